@@ -0,0 +1,79 @@
+use behaviour::registry::{Identifier, Registry};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const ENTRIES: usize = 10_000;
+
+fn ids() -> Vec<Identifier> {
+    (0..ENTRIES)
+        .map(|idx| Identifier::from(format!("bench:node_{idx}")))
+        .collect()
+}
+
+/// Lookups as the registry did them before it kept a hash index, by scanning
+/// every key in turn.
+fn linear_handle(keys: &[Identifier], id: &Identifier) -> Option<usize> {
+    keys.iter().position(|key| key == id)
+}
+
+fn lookup(c: &mut Criterion) {
+    let ids = ids();
+    let mut registry = Registry::with_capacity(ENTRIES);
+    for (idx, id) in ids.iter().enumerate() {
+        registry.insert(id, idx).unwrap();
+    }
+    let first = &ids[0];
+    let last = &ids[ENTRIES - 1];
+    let missing = Identifier::from("bench:missing");
+
+    let mut group = c.benchmark_group("get_handle");
+    group.bench_function("linear/first", |b| {
+        b.iter(|| linear_handle(&ids, black_box(first)))
+    });
+    group.bench_function("linear/last", |b| {
+        b.iter(|| linear_handle(&ids, black_box(last)))
+    });
+    group.bench_function("linear/missing", |b| {
+        b.iter(|| linear_handle(&ids, black_box(&missing)))
+    });
+    group.bench_function("hashed/first", |b| {
+        b.iter(|| registry.get_handle(black_box(first)))
+    });
+    group.bench_function("hashed/last", |b| {
+        b.iter(|| registry.get_handle(black_box(last)))
+    });
+    group.bench_function("hashed/missing", |b| {
+        b.iter(|| registry.get_handle(black_box(&missing)))
+    });
+    group.finish();
+}
+
+fn insert(c: &mut Criterion) {
+    let ids = ids();
+
+    let mut group = c.benchmark_group("insert");
+    group.sample_size(10);
+    group.bench_function("linear", |b| {
+        b.iter(|| {
+            let mut keys = Vec::with_capacity(ENTRIES);
+            for id in &ids {
+                if linear_handle(&keys, id).is_none() {
+                    keys.push(id.clone());
+                }
+            }
+            keys
+        })
+    });
+    group.bench_function("hashed", |b| {
+        b.iter(|| {
+            let mut registry = Registry::with_capacity(ENTRIES);
+            for (idx, id) in ids.iter().enumerate() {
+                registry.insert(id, idx).unwrap();
+            }
+            registry
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, lookup, insert);
+criterion_main!(benches);
@@ -0,0 +1,139 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use crate::identifier::Identifier;
+
+/// Typed key/value store shared by the nodes of one agent's tree.
+#[derive(Debug, Default)]
+pub struct Blackboard {
+    entries: HashMap<Identifier, Box<dyn Any + Send>>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: &Identifier) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    pub fn get<T: Any + Send>(&self, id: &Identifier) -> Result<&T, BlackboardError> {
+        self.entries
+            .get(id)
+            .ok_or_else(|| BlackboardError::MissingEntry(id.clone()))?
+            .downcast_ref()
+            .ok_or_else(|| BlackboardError::TypeMismatch(id.clone()))
+    }
+
+    pub fn get_mut<T: Any + Send>(&mut self, id: &Identifier) -> Result<&mut T, BlackboardError> {
+        self.entries
+            .get_mut(id)
+            .ok_or_else(|| BlackboardError::MissingEntry(id.clone()))?
+            .downcast_mut()
+            .ok_or_else(|| BlackboardError::TypeMismatch(id.clone()))
+    }
+
+    /// Stores `value`, returning the value it replaced. An existing entry of
+    /// another type is left untouched.
+    pub fn set<T: Any + Send>(
+        &mut self,
+        id: &Identifier,
+        value: T,
+    ) -> Result<Option<T>, BlackboardError> {
+        match self.get_mut::<T>(id) {
+            Ok(existing) => Ok(Some(std::mem::replace(existing, value))),
+            Err(BlackboardError::MissingEntry(_)) => {
+                self.entries.insert(id.clone(), Box::new(value));
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn remove<T: Any + Send>(&mut self, id: &Identifier) -> Result<T, BlackboardError> {
+        self.get::<T>(id)?;
+        let value = self.entries.remove(id).unwrap();
+        Ok(*value.downcast().unwrap())
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlackboardError {
+    MissingEntry(Identifier),
+    TypeMismatch(Identifier),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blackboard, BlackboardError};
+    use crate::identifier::Identifier;
+
+    #[test]
+    fn set_and_get() {
+        let mut subject = Blackboard::new();
+        let id = Identifier::from("target");
+
+        assert_eq!(subject.set(&id, 12u32), Ok(None));
+        assert!(subject.contains(&id));
+        assert_eq!(subject.get::<u32>(&id), Ok(&12));
+        assert_eq!(subject.set(&id, 13u32), Ok(Some(12)));
+        assert_eq!(subject.get::<u32>(&id), Ok(&13));
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut subject = Blackboard::new();
+        let id = Identifier::from("target");
+        subject.set(&id, 12u32).unwrap();
+
+        *subject.get_mut::<u32>(&id).unwrap() += 1;
+        assert_eq!(subject.get::<u32>(&id), Ok(&13));
+    }
+
+    #[test]
+    fn missing_entry() {
+        let subject = Blackboard::new();
+        let id = Identifier::from("target");
+
+        assert_eq!(
+            subject.get::<u32>(&id),
+            Err(BlackboardError::MissingEntry(id))
+        );
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let mut subject = Blackboard::new();
+        let id = Identifier::from("target");
+        subject.set(&id, 12u32).unwrap();
+
+        assert_eq!(
+            subject.get::<String>(&id),
+            Err(BlackboardError::TypeMismatch(id.clone()))
+        );
+        assert_eq!(
+            subject.set(&id, String::from("12")),
+            Err(BlackboardError::TypeMismatch(id.clone()))
+        );
+        assert_eq!(
+            subject.remove::<String>(&id),
+            Err(BlackboardError::TypeMismatch(id.clone()))
+        );
+        assert_eq!(subject.get::<u32>(&id), Ok(&12));
+    }
+
+    #[test]
+    fn remove() {
+        let mut subject = Blackboard::new();
+        let id = Identifier::from("target");
+        subject.set(&id, 12u32).unwrap();
+
+        assert_eq!(subject.remove::<u32>(&id), Ok(12));
+        assert!(!subject.contains(&id));
+    }
+}
@@ -0,0 +1,689 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Weak;
+
+use crate::{
+    context::BehaviourContext,
+    identifier::Identifier,
+    parameter::{Parameter, Parameters},
+    tree::{
+        BehaviourNode, BehaviourTree, TreeCompilationError, VecType, CONDITION_ID, DECORATOR_ID,
+        EXECUTOR_ID, ID_MASK, NODE_SIZE, OPCODE_SHIFT, OPCODE_VERSION,
+    },
+    verifier::{VerificationError, VerificationErrorKind},
+};
+
+const MAGIC: &[u8; 4] = b"BHVT";
+/// The version of the container layout written by `to_bytes`.
+const FORMAT_VERSION: u16 = 1;
+
+const INTEGER_TAG: u8 = 0;
+const FLOAT_TAG: u8 = 1;
+const TEXT_TAG: u8 = 2;
+const IDENTIFIER_TAG: u8 = 3;
+const BLACKBOARD_KEY_TAG: u8 = 4;
+
+#[derive(Debug, PartialEq)]
+pub enum BytecodeError {
+    InvalidMagic,
+    UnsupportedFormatVersion(u16),
+    /// The data was written for a different set of opcodes.
+    UnsupportedOpcodeVersion(u16),
+    /// The data ends part way through a section.
+    Truncated,
+    /// There is data left over after the code section.
+    TrailingBytes,
+    InvalidText,
+    InvalidParameterTag(u8),
+    /// The node at `offset` in the code refers to a symbol or constant that
+    /// does not exist, or the code does not hold a whole number of nodes.
+    MalformedCode {
+        offset: usize,
+    },
+    UnknownDecorator(Identifier),
+    UnknownExecutor(Identifier),
+    UnknownCondition(Identifier),
+    UnencodableRegistryHandle {
+        id: Identifier,
+        registry_index: usize,
+    },
+    NonExistentContext,
+    /// The parameters of the executor node at `offset` do not match the ports
+    /// its executor now declares in the context.
+    InvalidPorts {
+        offset: usize,
+        error: TreeCompilationError,
+    },
+    /// The code does not form a tree that can be ticked safely.
+    InvalidCode(VerificationError),
+}
+
+impl Display for BytecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "the data is not a compiled behaviour tree"),
+            Self::UnsupportedFormatVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            Self::UnsupportedOpcodeVersion(version) => {
+                write!(f, "unsupported opcode version {version}")
+            }
+            Self::Truncated => write!(f, "the data ends part way through a section"),
+            Self::TrailingBytes => write!(f, "there is data after the code"),
+            Self::InvalidText => write!(f, "a string is not valid UTF-8"),
+            Self::InvalidParameterTag(tag) => write!(f, "unknown parameter tag {tag}"),
+            Self::MalformedCode { offset } => write!(f, "malformed code at {offset}"),
+            Self::UnknownDecorator(id) => write!(f, "the decorator `{id}` is not registered"),
+            Self::UnknownExecutor(id) => write!(f, "the executor `{id}` is not registered"),
+            Self::UnknownCondition(id) => write!(f, "the condition `{id}` is not registered"),
+            Self::UnencodableRegistryHandle { id, registry_index } => write!(
+                f,
+                "`{id}` is registered at {registry_index}, past the last index an operand can hold"
+            ),
+            Self::NonExistentContext => write!(f, "the context no longer exists"),
+            Self::InvalidPorts { offset, error } => match error {
+                TreeCompilationError::MissingPort { node, port } => {
+                    write!(
+                        f,
+                        "node at {offset}: `{node}` has no parameter for the port `{port}`"
+                    )
+                }
+                TreeCompilationError::UnknownPort { node, port } => {
+                    write!(f, "node at {offset}: `{node}` has no port `{port}`")
+                }
+                TreeCompilationError::MistypedPort {
+                    node,
+                    port,
+                    expected,
+                } => write!(
+                    f,
+                    "node at {offset}: the port `{port}` of `{node}` expects {expected:?}"
+                ),
+                TreeCompilationError::UnmappedPort { node, port } => write!(
+                    f,
+                    "node at {offset}: the output port `{port}` of `{node}` needs a blackboard key"
+                ),
+                TreeCompilationError::ConflictingBlackboardKey {
+                    key,
+                    expected,
+                    found,
+                } => write!(
+                    f,
+                    "node at {offset}: the blackboard key `{key}` holds {expected:?}, not {found:?}"
+                ),
+                error => write!(f, "node at {offset}: {error:?}"),
+            },
+            Self::InvalidCode(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for BytecodeError {}
+
+/// The symbol table a node's operand indexes into on disk, if it refers to a
+/// registry entry.
+fn symbol_table(opcode: u8) -> Option<usize> {
+    match opcode {
+        EXECUTOR_ID => Some(0),
+        DECORATOR_ID => Some(1),
+        CONDITION_ID => Some(2),
+        _ => None,
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    write_u32(out, len as u32);
+}
+
+fn write_text(out: &mut Vec<u8>, text: &str) {
+    write_len(out, text.len());
+    out.extend_from_slice(text.as_bytes());
+}
+
+struct Input<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], BytecodeError> {
+        let (taken, rest) = self
+            .bytes
+            .split_first_chunk()
+            .ok_or(BytecodeError::Truncated)?;
+        self.bytes = rest;
+        Ok(*taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BytecodeError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, BytecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, BytecodeError> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    /// Reads a length, checked against the bytes left so corrupt data can't
+    /// cause huge allocations.
+    fn len(&mut self) -> Result<usize, BytecodeError> {
+        let len = self.u32()? as usize;
+        if len > self.bytes.len() {
+            return Err(BytecodeError::Truncated);
+        }
+        Ok(len)
+    }
+
+    fn text(&mut self) -> Result<String, BytecodeError> {
+        let len = self.len()?;
+        let (text, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        String::from_utf8(text.to_vec()).map_err(|_| BytecodeError::InvalidText)
+    }
+
+    fn parameter(&mut self) -> Result<Parameter, BytecodeError> {
+        Ok(match self.u8()? {
+            INTEGER_TAG => Parameter::Integer(self.u64()? as i64),
+            FLOAT_TAG => Parameter::Float(f64::from_bits(self.u64()?)),
+            TEXT_TAG => Parameter::Text(self.text()?),
+            IDENTIFIER_TAG => Parameter::Identifier(self.text()?.into()),
+            BLACKBOARD_KEY_TAG => Parameter::BlackboardKey(self.text()?.into()),
+            tag => return Err(BytecodeError::InvalidParameterTag(tag)),
+        })
+    }
+}
+
+impl<Calltype> BehaviourTree<Calltype> {
+    /// Saves the compiled tree so it can be loaded with `from_bytes` without
+    /// recompiling. Registry entries are stored by identifier rather than by
+    /// handle, so the tree can be loaded into a context whose entries were
+    /// registered in a different order.
+    ///
+    /// The layout is the magic `BHVT`, the format and opcode table versions,
+    /// the executor, decorator and condition symbol tables, the parameter
+    /// constant pool, the constant index of every node and finally the code,
+    /// all little endian.
+    ///
+    /// Fails with `BytecodeError::InvalidCode` if a node refers to a registry
+    /// entry that is not in the tree's context.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BytecodeError> {
+        let mut symbols: [Vec<&Identifier>; 3] = Default::default();
+        let mut indices: [HashMap<&Identifier, usize>; 3] = Default::default();
+        let mut code = self.code.clone();
+        for position in (0..code.len()).step_by(NODE_SIZE) {
+            let word = code[position];
+            let opcode = (word >> OPCODE_SHIFT) as u8;
+            let Some(table) = symbol_table(opcode) else {
+                continue;
+            };
+            let handle = self.handle(position);
+            let (id, kind): (_, fn(usize) -> VerificationErrorKind) = match opcode {
+                EXECUTOR_ID => (
+                    self.context.executor_id(&handle),
+                    VerificationErrorKind::UnknownExecutor,
+                ),
+                DECORATOR_ID => (
+                    self.context.decorator_id(&handle),
+                    VerificationErrorKind::UnknownDecorator,
+                ),
+                _ => (
+                    self.context.condition_id(&handle),
+                    VerificationErrorKind::UnknownCondition,
+                ),
+            };
+            let id = id.ok_or_else(|| {
+                BytecodeError::InvalidCode(VerificationError {
+                    offset: position,
+                    kind: kind(handle.value()),
+                })
+            })?;
+            let symbols = &mut symbols[table];
+            let index = *indices[table].entry(id).or_insert_with(|| {
+                symbols.push(id);
+                symbols.len() - 1
+            });
+            code[position] = (word & ID_MASK) | index as VecType;
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&OPCODE_VERSION.to_le_bytes());
+        for table in &symbols {
+            write_len(&mut out, table.len());
+            for &id in table {
+                write_text(&mut out, &String::from(id.clone()));
+            }
+        }
+        write_len(&mut out, self.constants.len());
+        for parameters in &self.constants {
+            write_len(&mut out, parameters.len());
+            for (name, parameter) in parameters.iter() {
+                write_text(&mut out, name);
+                match parameter {
+                    Parameter::Integer(value) => {
+                        out.push(INTEGER_TAG);
+                        out.extend_from_slice(&value.to_le_bytes());
+                    }
+                    Parameter::Float(value) => {
+                        out.push(FLOAT_TAG);
+                        out.extend_from_slice(&value.to_bits().to_le_bytes());
+                    }
+                    Parameter::Text(value) => {
+                        out.push(TEXT_TAG);
+                        write_text(&mut out, value);
+                    }
+                    Parameter::Identifier(id) => {
+                        out.push(IDENTIFIER_TAG);
+                        write_text(&mut out, &String::from(id.clone()));
+                    }
+                    Parameter::BlackboardKey(key) => {
+                        out.push(BLACKBOARD_KEY_TAG);
+                        write_text(&mut out, &String::from(key.clone()));
+                    }
+                }
+            }
+        }
+        write_len(&mut out, self.constant_indices.len());
+        for &index in &self.constant_indices {
+            write_u32(&mut out, index);
+        }
+        write_len(&mut out, code.len());
+        for word in code {
+            write_u32(&mut out, word);
+        }
+        Ok(out)
+    }
+
+    /// Loads a tree saved with `to_bytes`, resolving its executors, decorators
+    /// and conditions against `ctx` by identifier. The executors' parameters
+    /// are checked against the ports they now declare, and the code verified.
+    pub fn from_bytes(
+        bytes: &[u8],
+        ctx: Weak<BehaviourContext<Calltype>>,
+    ) -> Result<Self, BytecodeError> {
+        let context = ctx.upgrade().ok_or(BytecodeError::NonExistentContext)?;
+        let mut input = Input { bytes };
+        if &input.take::<4>()? != MAGIC {
+            return Err(BytecodeError::InvalidMagic);
+        }
+        match input.u16()? {
+            FORMAT_VERSION => {}
+            version => return Err(BytecodeError::UnsupportedFormatVersion(version)),
+        }
+        match input.u16()? {
+            OPCODE_VERSION => {}
+            version => return Err(BytecodeError::UnsupportedOpcodeVersion(version)),
+        }
+
+        let mut symbols: [Vec<Identifier>; 3] = Default::default();
+        for table in &mut symbols {
+            for _ in 0..input.len()? {
+                table.push(input.text()?.into());
+            }
+        }
+        let mut constants = Vec::new();
+        for _ in 0..input.len()? {
+            let mut parameters = Parameters::new();
+            for _ in 0..input.len()? {
+                let name = input.text()?;
+                parameters.insert(&name, input.parameter()?);
+            }
+            constants.push(parameters);
+        }
+        let mut constant_indices = Vec::new();
+        for _ in 0..input.len()? {
+            constant_indices.push(input.u32()?);
+        }
+        let mut code = Vec::new();
+        for _ in 0..input.len()? {
+            code.push(input.u32()?);
+        }
+        if !input.bytes.is_empty() {
+            return Err(BytecodeError::TrailingBytes);
+        }
+
+        let node_count = code.len() / NODE_SIZE;
+        if code.is_empty() || code.len() % NODE_SIZE != 0 {
+            return Err(BytecodeError::MalformedCode {
+                offset: node_count * NODE_SIZE,
+            });
+        }
+        if constant_indices.len() != node_count {
+            let offset = node_count.min(constant_indices.len()) * NODE_SIZE;
+            return Err(BytecodeError::MalformedCode { offset });
+        }
+        let mut handle_generations = vec![0; node_count];
+        // As when compiling, the type each blackboard key is used as by the
+        // ports mapped to it.
+        let mut key_types = HashMap::new();
+        for position in (0..code.len()).step_by(NODE_SIZE) {
+            if constant_indices[position / NODE_SIZE] as usize >= constants.len() {
+                return Err(BytecodeError::MalformedCode { offset: position });
+            }
+            let word = code[position];
+            let opcode = (word >> OPCODE_SHIFT) as u8;
+            let Some(table) = symbol_table(opcode) else {
+                continue;
+            };
+            let id = symbols[table]
+                .get((word & !ID_MASK) as usize)
+                .ok_or(BytecodeError::MalformedCode { offset: position })?;
+            let handle = match opcode {
+                EXECUTOR_ID => context
+                    .get_executor_handle(id)
+                    .ok_or_else(|| BytecodeError::UnknownExecutor(id.clone())),
+                DECORATOR_ID => context
+                    .get_decorator_handle(id)
+                    .ok_or_else(|| BytecodeError::UnknownDecorator(id.clone())),
+                _ => context
+                    .get_condition_handle(id)
+                    .ok_or_else(|| BytecodeError::UnknownCondition(id.clone())),
+            }?;
+            if handle.value() & ID_MASK as usize != 0 {
+                return Err(BytecodeError::UnencodableRegistryHandle {
+                    id: id.clone(),
+                    registry_index: handle.value(),
+                });
+            }
+            // The executor's ports may have changed since the tree was saved.
+            if let (EXECUTOR_ID, Some(ports)) = (opcode, context.executor_ports(&handle)) {
+                let parameters = &constants[constant_indices[position / NODE_SIZE] as usize];
+                BehaviourNode::check_ports(id, ports, parameters, &mut key_types).map_err(
+                    |error| BytecodeError::InvalidPorts {
+                        offset: position,
+                        error,
+                    },
+                )?;
+            }
+            code[position] = (word & ID_MASK) | handle.value() as VecType;
+            handle_generations[position / NODE_SIZE] = handle.generation();
+        }
+
+        let tree = BehaviourTree {
+            code,
+            constants,
+            constant_indices,
+            handle_generations,
+            context,
+            node_count,
+        };
+        tree.verify().map_err(BytecodeError::InvalidCode)?;
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        blackboard::Blackboard,
+        context::{BehaviourContext, ExecutorOptions},
+        parameter::{Parameter, Parameters, Port, PortType},
+        state::{TreeResult, TreeState},
+        tree::{BehaviourNode as Node, BehaviourTree, TreeCompilationError},
+        verifier::{VerificationError, VerificationErrorKind},
+    };
+
+    use super::BytecodeError;
+
+    fn record(parameters: &Parameters, blackboard: &mut Blackboard, _: ()) -> TreeResult {
+        let key = parameters.blackboard_key("into").unwrap();
+        let value = parameters.text("value").unwrap().to_string();
+        blackboard.set(key, value).unwrap();
+        TreeResult::Success
+    }
+
+    fn succeed(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+        TreeResult::Success
+    }
+
+    fn keep(result: TreeResult, _: &Parameters, _: ()) -> TreeResult {
+        result
+    }
+
+    fn ready(_: &Blackboard, _: ()) -> bool {
+        true
+    }
+
+    /// A context whose entries are registered in an order given by `reversed`,
+    /// so their handles differ between the two orders.
+    fn context(reversed: bool) -> Arc<BehaviourContext<()>> {
+        let mut context = BehaviourContext::new();
+        let mut executors = ["unused", "succeed", "record"];
+        if reversed {
+            executors.reverse();
+        }
+        for id in executors {
+            match id {
+                "record" => context.register_executor(&id.into(), record),
+                _ => context.register_executor(&id.into(), succeed),
+            }
+            .unwrap();
+        }
+        context.register_decorator(&"keep".into(), keep).unwrap();
+        context.register_condition(&"ready".into(), ready).unwrap();
+        Arc::new(context)
+    }
+
+    fn tree(ctx: &Arc<BehaviourContext<()>>) -> BehaviourTree<()> {
+        let record = |value: &str| {
+            Node::Executor(
+                "record".into(),
+                Parameters::new()
+                    .with("into", Parameter::BlackboardKey(value.into()))
+                    .with("value", value)
+                    .with("scale", 1.5)
+                    .with("count", -2)
+                    .with("who", Parameter::Identifier("npc:guard".into())),
+            )
+        };
+        let succeed = || Node::Executor("succeed".into(), Parameters::new());
+        Node::Root(Box::new(Node::Sequence {
+            children: vec![
+                Node::Condition("ready".into()),
+                succeed(),
+                Node::Decorator {
+                    name: "keep".into(),
+                    parameters: Parameters::new(),
+                    child: Box::new(succeed()),
+                },
+                record("first"),
+                record("second"),
+            ],
+        }))
+        .compile(Arc::downgrade(ctx))
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trips() {
+        let saved = context(false);
+        let subject = tree(&saved);
+        let bytes = subject.to_bytes().unwrap();
+
+        let loaded_ctx = context(true);
+        let loaded = BehaviourTree::from_bytes(&bytes, Arc::downgrade(&loaded_ctx)).unwrap();
+        // The executors were registered in the opposite order, so their
+        // handles differ from those in the saved code.
+        assert_ne!(loaded.code(), subject.code());
+        assert_eq!(loaded.constants(), subject.constants());
+        assert_eq!(loaded.to_bytes(), Ok(bytes));
+
+        let mut state = TreeState::new(&loaded);
+        assert_eq!(loaded.tick(&mut state, ()), TreeResult::Success);
+        let blackboard = state.blackboard();
+        assert_eq!(
+            blackboard.get::<String>(&"first".into()),
+            Ok(&"first".to_string())
+        );
+        assert_eq!(
+            blackboard.get::<String>(&"second".into()),
+            Ok(&"second".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let ctx = context(false);
+        let bytes = tree(&ctx).to_bytes().unwrap();
+        let load = |bytes: &[u8]| BehaviourTree::from_bytes(bytes, Arc::downgrade(&ctx)).err();
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert_eq!(load(&magic), Some(BytecodeError::InvalidMagic));
+
+        let mut format = bytes.clone();
+        format[4] = 9;
+        assert_eq!(
+            load(&format),
+            Some(BytecodeError::UnsupportedFormatVersion(9))
+        );
+
+        let mut opcodes = bytes.clone();
+        opcodes[6] = 9;
+        assert_eq!(
+            load(&opcodes),
+            Some(BytecodeError::UnsupportedOpcodeVersion(9))
+        );
+
+        assert_eq!(
+            load(&bytes[..bytes.len() - 1]),
+            Some(BytecodeError::Truncated)
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(load(&trailing), Some(BytecodeError::TrailingBytes));
+    }
+
+    #[test]
+    fn rejects_malformed_code() {
+        let ctx = context(false);
+        let subject = tree(&ctx);
+        let bytes = subject.to_bytes().unwrap();
+        let load = |bytes: &[u8]| BehaviourTree::from_bytes(bytes, Arc::downgrade(&ctx)).err();
+
+        // The code is last, and its second node is the condition, whose operand
+        // indexes the single entry of the condition symbol table.
+        let code = bytes.len() - subject.code().len() * 4;
+        let mut condition = bytes.clone();
+        condition[code + 8] = 1;
+        assert_eq!(
+            load(&condition),
+            Some(BytecodeError::MalformedCode { offset: 2 })
+        );
+
+        // Dropping the last node leaves a constant index without a node.
+        let mut short = bytes[..bytes.len() - 8].to_vec();
+        short[code - 4..code].copy_from_slice(&(subject.code().len() as u32 - 2).to_le_bytes());
+        assert_eq!(
+            load(&short),
+            Some(BytecodeError::MalformedCode {
+                offset: subject.code().len() - 2
+            })
+        );
+    }
+
+    #[test]
+    fn rechecks_ports() {
+        let bytes = tree(&context(false)).to_bytes().unwrap();
+
+        let mut changed = BehaviourContext::new();
+        changed
+            .register_executor(&"succeed".into(), succeed)
+            .unwrap();
+        changed
+            .register_executor_with_options(
+                &"record".into(),
+                record,
+                ExecutorOptions::new().ports([
+                    Port::output("into", PortType::Text),
+                    Port::input("value", PortType::Text),
+                    Port::input("scale", PortType::Float),
+                    Port::input("count", PortType::Integer),
+                    Port::input("who", PortType::Identifier),
+                    Port::input("volume", PortType::Float),
+                ]),
+            )
+            .unwrap();
+        changed.register_decorator(&"keep".into(), keep).unwrap();
+        changed.register_condition(&"ready".into(), ready).unwrap();
+        let changed = Arc::new(changed);
+
+        // The first `record` node follows the sequence and its three other
+        // children.
+        assert_eq!(
+            BehaviourTree::from_bytes(&bytes, Arc::downgrade(&changed)).err(),
+            Some(BytecodeError::InvalidPorts {
+                offset: 8,
+                error: TreeCompilationError::MissingPort {
+                    node: "record".into(),
+                    port: "volume".into()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_symbols() {
+        let ctx = context(false);
+        let bytes = tree(&ctx).to_bytes().unwrap();
+
+        let mut missing = BehaviourContext::new();
+        missing
+            .register_executor(&"succeed".into(), succeed)
+            .unwrap();
+        missing.register_decorator(&"keep".into(), keep).unwrap();
+        missing.register_condition(&"ready".into(), ready).unwrap();
+        let missing = Arc::new(missing);
+        assert_eq!(
+            BehaviourTree::from_bytes(&bytes, Arc::downgrade(&missing)).err(),
+            Some(BytecodeError::UnknownExecutor("record".into()))
+        );
+    }
+    #[test]
+    fn refuses_to_save_stale_handles() {
+        let ctx = context(false);
+        let mut subject = tree(&ctx);
+        // The `succeed` node that follows the condition.
+        subject.handle_generations[2] += 1;
+
+        assert_eq!(
+            subject.to_bytes(),
+            Err(BytecodeError::InvalidCode(VerificationError {
+                offset: 4,
+                kind: VerificationErrorKind::UnknownExecutor(subject.handle(4).value()),
+            }))
+        );
+    }
+
+    #[test]
+    fn describes_errors() {
+        assert_eq!(
+            BytecodeError::UnknownExecutor("record".into()).to_string(),
+            "the executor `game:record` is not registered"
+        );
+        assert_eq!(
+            BytecodeError::InvalidPorts {
+                offset: 8,
+                error: TreeCompilationError::MissingPort {
+                    node: "record".into(),
+                    port: "volume".into()
+                }
+            }
+            .to_string(),
+            "node at 8: `game:record` has no parameter for the port `volume`"
+        );
+    }
+}
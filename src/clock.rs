@@ -0,0 +1,77 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Source of the current time consulted by time based nodes while ticking.
+pub trait Clock {
+    /// Time elapsed since an arbitrary, fixed epoch.
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic time, measured from the first time any `SystemClock` is read in
+/// the process. Unlike wall clock time it never jumps when the system clock
+/// is changed, so timeouts and cooldowns are unaffected.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic ticking.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ManualClock {
+    now: Duration,
+}
+
+impl ManualClock {
+    pub fn new(now: Duration) -> Self {
+        Self { now }
+    }
+
+    pub fn set(&mut self, now: Duration) {
+        self.now = now;
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Clock, ManualClock, SystemClock};
+
+    #[test]
+    fn manual_clock() {
+        let mut subject = ManualClock::new(Duration::from_secs(1));
+        assert_eq!(subject.now(), Duration::from_secs(1));
+
+        subject.advance(Duration::from_millis(500));
+        assert_eq!(subject.now(), Duration::from_millis(1500));
+
+        subject.set(Duration::ZERO);
+        assert_eq!(subject.now(), Duration::ZERO);
+    }
+
+    #[test]
+    fn system_clock() {
+        let subject = SystemClock;
+        let first = subject.now();
+        std::thread::sleep(Duration::from_millis(2));
+        let second = subject.now();
+        assert!(second >= first + Duration::from_millis(2));
+        // Every clock shares the same start.
+        assert!(SystemClock.now() >= second);
+    }
+}
@@ -1,110 +1,698 @@
-use std::marker::Tuple;
-use std::ops::Fn;
+use crate::blackboard::Blackboard;
+use crate::parameter::{Parameters, Port};
+use crate::registry::{
+    Identifier, Registry, RegistryHandle, RegistryInsertError, RegistryReplaceError,
+};
 
-use crate::registry::{Identifier, Registry, RegistryHandle, RegistryInsertError};
-
-pub trait NodeHandler<Args: Tuple, ReturnType>: Fn<Args, Output = ReturnType> {}
+/// A handler that can be registered with a `BehaviourContext`, called with
+/// the arguments of its node as a tuple.
+///
+/// Implemented for every two and three argument function or closure, so plain
+/// functions and closures capturing configuration or state can be registered
+/// directly. Closures taking references need their types annotated, e.g.
+/// `|parameters: &Parameters, blackboard: &mut Blackboard, args| ...`. Types carrying their own state or
+/// configuration can implement it themselves.
+pub trait NodeHandler<Args, ReturnType> {
+    fn call(&self, args: Args) -> ReturnType;
+}
 
-impl<CallType: Tuple, ReturnType> std::fmt::Debug for Registry<fn(CallType) -> ReturnType> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Registry").finish()
+impl<First, Second, ReturnType, T> NodeHandler<(First, Second), ReturnType> for T
+where
+    T: Fn(First, Second) -> ReturnType,
+{
+    fn call(&self, (first, second): (First, Second)) -> ReturnType {
+        self(first, second)
     }
 }
 
-impl<CallType: Tuple, ReturnType> std::fmt::Debug
-    for Registry<fn(ReturnType, CallType) -> ReturnType>
+impl<First, Second, Third, ReturnType, T> NodeHandler<(First, Second, Third), ReturnType> for T
+where
+    T: Fn(First, Second, Third) -> ReturnType,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Registry").finish()
+    fn call(&self, (first, second, third): (First, Second, Third)) -> ReturnType {
+        self(first, second, third)
+    }
+}
+
+// Handlers are shared by every thread ticking a tree, hence `Send + Sync`.
+type ExecutorHandler<CallType, ReturnType> = Box<
+    dyn for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ReturnType>
+        + Send
+        + Sync,
+>;
+type HaltHandler<CallType> = Box<
+    dyn for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ()> + Send + Sync,
+>;
+type DecoratorHandler<CallType, ReturnType> =
+    Box<dyn for<'a> NodeHandler<(ReturnType, &'a Parameters, CallType), ReturnType> + Send + Sync>;
+type ConditionHandler<CallType> =
+    Box<dyn for<'a> NodeHandler<(&'a Blackboard, CallType), bool> + Send + Sync>;
+
+/// A registered executor, the callback run when it is abandoned while
+/// running and the ports it declares, if any.
+struct Executor<CallType, ReturnType> {
+    handle: ExecutorHandler<CallType, ReturnType>,
+    halt: Option<HaltHandler<CallType>>,
+    ports: Option<Vec<Port>>,
+}
+
+/// The optional parts of an executor's registration.
+pub struct ExecutorOptions<CallType> {
+    halt: Option<HaltHandler<CallType>>,
+    ports: Option<Vec<Port>>,
+}
+
+impl<CallType> ExecutorOptions<CallType> {
+    pub fn new() -> Self {
+        Self {
+            halt: None,
+            ports: None,
+        }
+    }
+
+    /// Sets a callback run whenever the tree abandons the executor while it is
+    /// running, so it can release what it holds.
+    pub fn halt(
+        mut self,
+        halt: impl for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ()>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.halt = Some(Box::new(halt));
+        self
+    }
+
+    /// Declares the ports the executor expects. Trees using it only compile if
+    /// its nodes give a parameter for exactly these ports.
+    pub fn ports(mut self, ports: impl IntoIterator<Item = Port>) -> Self {
+        self.ports = Some(ports.into_iter().collect());
+        self
+    }
+}
+
+impl<CallType> Default for ExecutorOptions<CallType> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[derive(Debug)]
-pub struct BehaviourContext<CallType: Tuple, ReturnType = crate::state::TreeResult> {
-    executors: Registry<fn(CallType) -> ReturnType>,
-    decorators: Registry<fn(ReturnType, CallType) -> ReturnType>,
+pub struct BehaviourContext<CallType, ReturnType = crate::state::TreeResult> {
+    executors: Registry<Executor<CallType, ReturnType>>,
+    decorators: Registry<DecoratorHandler<CallType, ReturnType>>,
+    conditions: Registry<ConditionHandler<CallType>>,
 }
 
-impl<CallType: Tuple, ReturnType> BehaviourContext<CallType, ReturnType> {
+impl<CallType, ReturnType> BehaviourContext<CallType, ReturnType> {
     pub fn new() -> Self {
         Self {
             executors: Registry::new(),
             decorators: Registry::new(),
+            conditions: Registry::new(),
         }
     }
 
-    pub fn with_capacity(handler_capacity: usize, decorator_capacity: usize) -> Self {
+    pub fn with_capacity(
+        handler_capacity: usize,
+        decorator_capacity: usize,
+        condition_capacity: usize,
+    ) -> Self {
         Self {
             executors: Registry::with_capacity(handler_capacity),
             decorators: Registry::with_capacity(decorator_capacity),
+            conditions: Registry::with_capacity(condition_capacity),
         }
     }
 
     pub fn register_executor(
         &mut self,
         id: &Identifier,
-        handle: fn(CallType) -> ReturnType,
+        handle: impl for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
     ) -> Result<(), RegistryInsertError> {
-        self.executors.insert(&id, handle)
+        self.register_executor_with_options(id, handle, ExecutorOptions::new())
+    }
+
+    /// Registers an executor along with its halt callback and ports, if it
+    /// has either.
+    pub fn register_executor_with_options(
+        &mut self,
+        id: &Identifier,
+        handle: impl for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
+        options: ExecutorOptions<CallType>,
+    ) -> Result<(), RegistryInsertError> {
+        self.executors.insert(
+            id,
+            Executor {
+                handle: Box::new(handle),
+                halt: options.halt,
+                ports: options.ports,
+            },
+        )
     }
 
     pub fn register_decorator(
         &mut self,
         id: &Identifier,
-        decorator: fn(ReturnType, CallType) -> ReturnType,
+        decorator: impl for<'a> NodeHandler<(ReturnType, &'a Parameters, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<(), RegistryInsertError> {
+        self.decorators.insert(id, Box::new(decorator))
+    }
+
+    /// Registers a condition. Conditions only inspect the world, so they
+    /// complete immediately and cannot leave the tree running.
+    pub fn register_condition(
+        &mut self,
+        id: &Identifier,
+        condition: impl for<'a> NodeHandler<(&'a Blackboard, CallType), bool> + Send + Sync + 'static,
     ) -> Result<(), RegistryInsertError> {
-        self.decorators.insert(&id, decorator)
+        self.conditions.insert(id, Box::new(condition))
+    }
+
+    /// Replaces the function of the executor registered under `id` with
+    /// `handle`, keeping the halt callback and ports it was registered with.
+    ///
+    /// Handles to the old executor go stale. Trees keep the context they were
+    /// built against from changing, so this is only possible once none are
+    /// left, and they need compiling or loading again afterwards. The same
+    /// goes for the other `replace_*` and `remove_*` methods.
+    pub fn replace_executor(
+        &mut self,
+        id: &Identifier,
+        handle: impl for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<(), RegistryReplaceError> {
+        self.executors.replace_with(id, |executor| Executor {
+            handle: Box::new(handle),
+            ..executor
+        })
+    }
+
+    /// Replaces the executor registered under `id` along with its halt
+    /// callback and ports, which are only kept if `options` gives them again.
+    pub fn replace_executor_with_options(
+        &mut self,
+        id: &Identifier,
+        handle: impl for<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
+        options: ExecutorOptions<CallType>,
+    ) -> Result<(), RegistryReplaceError> {
+        self.executors
+            .replace(
+                id,
+                Executor {
+                    handle: Box::new(handle),
+                    halt: options.halt,
+                    ports: options.ports,
+                },
+            )
+            .map(drop)
+    }
+
+    pub fn replace_decorator(
+        &mut self,
+        id: &Identifier,
+        decorator: impl for<'a> NodeHandler<(ReturnType, &'a Parameters, CallType), ReturnType>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<(), RegistryReplaceError> {
+        self.decorators.replace(id, Box::new(decorator)).map(drop)
+    }
+
+    pub fn replace_condition(
+        &mut self,
+        id: &Identifier,
+        condition: impl for<'a> NodeHandler<(&'a Blackboard, CallType), bool> + Send + Sync + 'static,
+    ) -> Result<(), RegistryReplaceError> {
+        self.conditions.replace(id, Box::new(condition)).map(drop)
+    }
+
+    /// Unregisters an executor, returning whether it was registered.
+    pub fn remove_executor(&mut self, id: &Identifier) -> bool {
+        self.executors.remove(id).is_some()
+    }
+
+    pub fn remove_decorator(&mut self, id: &Identifier) -> bool {
+        self.decorators.remove(id).is_some()
+    }
+
+    pub fn remove_condition(&mut self, id: &Identifier) -> bool {
+        self.conditions.remove(id).is_some()
     }
 
     pub fn get_executor_handle(&self, id: &Identifier) -> Option<RegistryHandle> {
-        self.executors.get_handle(&id)
+        self.executors.get_handle(id)
     }
 
     pub fn get_decorator_handle(&self, id: &Identifier) -> Option<RegistryHandle> {
-        self.decorators.get_handle(&id)
+        self.decorators.get_handle(id)
     }
 
-    pub fn call_executor(&self, handle: &RegistryHandle, args: CallType) -> ReturnType {
-        self.executors.get(handle).unwrap()(args)
+    pub fn get_condition_handle(&self, id: &Identifier) -> Option<RegistryHandle> {
+        self.conditions.get_handle(id)
+    }
+
+    pub fn executor_id(&self, handle: &RegistryHandle) -> Option<&Identifier> {
+        self.executors.get_key(handle)
+    }
+
+    pub fn decorator_id(&self, handle: &RegistryHandle) -> Option<&Identifier> {
+        self.decorators.get_key(handle)
+    }
+
+    pub fn condition_id(&self, handle: &RegistryHandle) -> Option<&Identifier> {
+        self.conditions.get_key(handle)
+    }
+
+    /// The ports declared by an executor, or `None` if it declared none.
+    pub fn executor_ports(&self, handle: &RegistryHandle) -> Option<&[Port]> {
+        self.executors.get(handle)?.ports.as_deref()
+    }
+
+    /// Runs an executor, or returns `None` without running anything if the
+    /// handle is stale or was never issued. The same goes for the other
+    /// `call_*` methods.
+    pub fn call_executor(
+        &self,
+        handle: &RegistryHandle,
+        parameters: &Parameters,
+        blackboard: &mut Blackboard,
+        args: CallType,
+    ) -> Option<ReturnType> {
+        let executor = self.executors.get(handle)?;
+        Some(executor.handle.call((parameters, blackboard, args)))
+    }
+
+    /// Runs an executor's halt callback, if it has one.
+    pub fn call_halt(
+        &self,
+        handle: &RegistryHandle,
+        parameters: &Parameters,
+        blackboard: &mut Blackboard,
+        args: CallType,
+    ) -> Option<()> {
+        if let Some(halt) = &self.executors.get(handle)?.halt {
+            halt.call((parameters, blackboard, args))
+        }
+        Some(())
     }
 
     pub fn call_decorator(
         &self,
         handle: &RegistryHandle,
+        parameters: &Parameters,
         args: CallType,
         result: ReturnType,
-    ) -> ReturnType {
-        self.decorators.get(handle).unwrap()(result, args)
+    ) -> Option<ReturnType> {
+        let decorator = self.decorators.get(handle)?;
+        Some(decorator.call((result, parameters, args)))
+    }
+
+    pub fn call_condition(
+        &self,
+        handle: &RegistryHandle,
+        blackboard: &Blackboard,
+        args: CallType,
+    ) -> Option<bool> {
+        let condition = self.conditions.get(handle)?;
+        Some(condition.call((blackboard, args)))
     }
 
     pub fn clear(&mut self) {
         self.executors.clear();
         self.decorators.clear();
+        self.conditions.clear();
+    }
+}
+
+impl<CallType, ReturnType> Default for BehaviourContext<CallType, ReturnType> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{identifier::Identifier, state::TreeResult};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
 
-    use super::BehaviourContext;
+    use crate::{
+        blackboard::Blackboard,
+        identifier::Identifier,
+        parameter::{Parameters, Port, PortType},
+        registry::RegistryReplaceError,
+        state::TreeResult,
+    };
+
+    use super::{BehaviourContext, ExecutorOptions, NodeHandler};
     type Subject = BehaviourContext<(i32, i32)>;
 
     #[test]
     fn calls_correctly() {
-        fn test_func((_, _1): (i32, i32)) -> TreeResult {
+        fn test_func(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
             TreeResult::Success
         }
 
         let mut subject = Subject::new();
         subject
-            .executors
-            .insert(&Identifier::from("Test"), test_func)
+            .register_executor(&Identifier::from("Test"), test_func)
             .unwrap();
         let handle = subject
             .executors
             .get_handle(&Identifier::from("Test"))
             .unwrap();
-        assert_eq!(subject.call_executor(&handle, (1, 2)), TreeResult::Success);
+        assert_eq!(
+            subject.call_executor(&handle, &Parameters::new(), &mut Blackboard::new(), (1, 2)),
+            Some(TreeResult::Success)
+        );
+    }
+
+    #[test]
+    fn calls_closures_correctly() {
+        fn move_to(speed: i32) -> impl Fn(&Parameters, &mut Blackboard, (i32, i32)) -> TreeResult {
+            move |_, blackboard, (from, to)| {
+                blackboard.set(&"position".into(), from + speed).unwrap();
+                if from + speed >= to {
+                    TreeResult::Success
+                } else {
+                    TreeResult::Running
+                }
+            }
+        }
+
+        let mut subject = Subject::new();
+        subject
+            .register_executor(&"walk".into(), move_to(1))
+            .unwrap();
+        subject
+            .register_executor(&"run".into(), move_to(3))
+            .unwrap();
+        let mut blackboard = Blackboard::new();
+
+        let walk = subject.get_executor_handle(&"walk".into()).unwrap();
+        let run = subject.get_executor_handle(&"run".into()).unwrap();
+        assert_eq!(
+            subject.call_executor(&walk, &Parameters::new(), &mut blackboard, (0, 2)),
+            Some(TreeResult::Running)
+        );
+        assert_eq!(blackboard.get::<i32>(&"position".into()), Ok(&1));
+        assert_eq!(
+            subject.call_executor(&run, &Parameters::new(), &mut blackboard, (0, 2)),
+            Some(TreeResult::Success)
+        );
+        assert_eq!(blackboard.get::<i32>(&"position".into()), Ok(&3));
+    }
+
+    #[test]
+    fn calls_handler_types_correctly() {
+        struct MoveTo {
+            speed: u32,
+        }
+
+        impl<'a, 'b> NodeHandler<(&'a Parameters, &'b mut Blackboard, u32), TreeResult> for MoveTo {
+            fn call(
+                &self,
+                (_, blackboard, target): (&'a Parameters, &'b mut Blackboard, u32),
+            ) -> TreeResult {
+                let position = blackboard.get::<u32>(&"position".into()).map_or(0, |p| *p);
+                blackboard
+                    .set(&"position".into(), position + self.speed)
+                    .unwrap();
+                if position + self.speed >= target {
+                    TreeResult::Success
+                } else {
+                    TreeResult::Running
+                }
+            }
+        }
+
+        let mut subject: BehaviourContext<u32> = BehaviourContext::new();
+        subject
+            .register_executor(&"walk".into(), MoveTo { speed: 2 })
+            .unwrap();
+        let handle = subject.get_executor_handle(&"walk".into()).unwrap();
+        let mut blackboard = Blackboard::new();
+
+        assert_eq!(
+            subject.call_executor(&handle, &Parameters::new(), &mut blackboard, 3),
+            Some(TreeResult::Running)
+        );
+        assert_eq!(
+            subject.call_executor(&handle, &Parameters::new(), &mut blackboard, 3),
+            Some(TreeResult::Success)
+        );
+    }
+
+    #[test]
+    fn calls_stateful_closures_correctly() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+
+        let mut subject = Subject::new();
+        subject
+            .register_decorator(&"count".into(), move |result, _: &Parameters, _| {
+                counted.fetch_add(1, Ordering::Relaxed);
+                result
+            })
+            .unwrap();
+
+        let handle = subject.get_decorator_handle(&"count".into()).unwrap();
+        subject.call_decorator(&handle, &Parameters::new(), (1, 2), TreeResult::Success);
+        subject.call_decorator(&handle, &Parameters::new(), (1, 2), TreeResult::Failure);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn replaces_and_removes_entries() {
+        fn success(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Success
+        }
+
+        fn failure(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Failure
+        }
+
+        let mut subject = Subject::new();
+        let id = Identifier::from("test");
+        subject.register_executor(&id, success).unwrap();
+        let stale = subject.get_executor_handle(&id).unwrap();
+
+        subject
+            .replace_executor_with_options(
+                &id,
+                failure,
+                ExecutorOptions::new().ports([Port::input("speed", PortType::Float)]),
+            )
+            .unwrap();
+        let handle = subject.get_executor_handle(&id).unwrap();
+        assert_ne!(handle, stale);
+        assert_eq!(subject.executor_id(&stale), None);
+        assert_eq!(subject.executor_ports(&handle).map(<[_]>::len), Some(1));
+        assert_eq!(
+            subject.call_executor(&handle, &Parameters::new(), &mut Blackboard::new(), (1, 2)),
+            Some(TreeResult::Failure)
+        );
+
+        assert!(subject.remove_executor(&id));
+        assert!(!subject.remove_executor(&id));
+        assert_eq!(subject.get_executor_handle(&id), None);
+        assert_eq!(
+            subject.replace_executor(&id, success),
+            Err(RegistryReplaceError::EntryMissing)
+        );
+    }
+
+    #[test]
+    fn replacing_keeps_halt_and_ports() {
+        fn running(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Running
+        }
+
+        fn success(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Success
+        }
+
+        fn halt(_: &Parameters, blackboard: &mut Blackboard, _: (i32, i32)) {
+            blackboard.set(&"halted".into(), true).unwrap();
+        }
+
+        let ports = [Port::input("speed", PortType::Float)];
+        let mut subject = Subject::new();
+        let id = Identifier::from("test");
+        subject
+            .register_executor_with_options(
+                &id,
+                running,
+                ExecutorOptions::new().halt(halt).ports(ports.clone()),
+            )
+            .unwrap();
+
+        subject.replace_executor(&id, success).unwrap();
+
+        let handle = subject.get_executor_handle(&id).unwrap();
+        let mut blackboard = Blackboard::new();
+        assert_eq!(
+            subject.call_executor(&handle, &Parameters::new(), &mut blackboard, (1, 2)),
+            Some(TreeResult::Success)
+        );
+        assert_eq!(subject.executor_ports(&handle), Some(&ports[..]));
+        subject.call_halt(&handle, &Parameters::new(), &mut blackboard, (1, 2));
+        assert_eq!(blackboard.get::<bool>(&"halted".into()), Ok(&true));
+    }
+
+    #[test]
+    fn rejects_stale_handles() {
+        fn success(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Success
+        }
+
+        let mut subject = Subject::new();
+        let id = Identifier::from("test");
+        subject.register_executor(&id, success).unwrap();
+        subject
+            .register_condition(&id, |_: &Blackboard, _| true)
+            .unwrap();
+        let executor = subject.get_executor_handle(&id).unwrap();
+        let condition = subject.get_condition_handle(&id).unwrap();
+        subject.clear();
+        subject.register_executor(&id, success).unwrap();
+        subject
+            .register_condition(&id, |_: &Blackboard, _| false)
+            .unwrap();
+
+        let mut blackboard = Blackboard::new();
+        assert_eq!(
+            subject.call_executor(&executor, &Parameters::new(), &mut blackboard, (1, 2)),
+            None
+        );
+        assert_eq!(
+            subject.call_halt(&executor, &Parameters::new(), &mut blackboard, (1, 2)),
+            None
+        );
+        assert_eq!(
+            subject.call_condition(&condition, &blackboard, (1, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn calls_halt_correctly() {
+        fn test_func(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Running
+        }
+
+        fn halt(_: &Parameters, blackboard: &mut Blackboard, (left, right): (i32, i32)) {
+            blackboard.set(&"halted".into(), left + right).unwrap();
+        }
+
+        let mut subject = Subject::new();
+        subject
+            .register_executor(&Identifier::from("Test"), test_func)
+            .unwrap();
+        subject
+            .register_executor_with_options(
+                &Identifier::from("Halt"),
+                test_func,
+                ExecutorOptions::new().halt(halt),
+            )
+            .unwrap();
+        let mut blackboard = Blackboard::new();
+
+        let handle = subject.get_executor_handle(&"Test".into()).unwrap();
+        subject.call_halt(&handle, &Parameters::new(), &mut blackboard, (1, 2));
+        assert!(!blackboard.contains(&"halted".into()));
+
+        let handle = subject.get_executor_handle(&"Halt".into()).unwrap();
+        subject.call_halt(&handle, &Parameters::new(), &mut blackboard, (1, 2));
+        assert_eq!(blackboard.get::<i32>(&"halted".into()), Ok(&3));
+    }
+
+    #[test]
+    fn calls_with_parameters() {
+        fn scale(
+            parameters: &Parameters,
+            blackboard: &mut Blackboard,
+            (left, _): (i32, i32),
+        ) -> TreeResult {
+            let factor = parameters.integer("factor").unwrap() as i32;
+            blackboard.set(&"scaled".into(), left * factor).unwrap();
+            TreeResult::Success
+        }
+
+        let mut subject = Subject::new();
+        subject.register_executor(&"scale".into(), scale).unwrap();
+        let handle = subject.get_executor_handle(&"scale".into()).unwrap();
+        let mut blackboard = Blackboard::new();
+
+        let parameters = Parameters::new().with("factor", 3);
+        subject.call_executor(&handle, &parameters, &mut blackboard, (2, 0));
+        assert_eq!(blackboard.get::<i32>(&"scaled".into()), Ok(&6));
+    }
+
+    #[test]
+    fn declares_ports() {
+        fn test_func(_: &Parameters, _: &mut Blackboard, _: (i32, i32)) -> TreeResult {
+            TreeResult::Success
+        }
+
+        let ports = [
+            Port::input("speed", PortType::Float),
+            Port::output("position", PortType::Integer),
+        ];
+        let mut subject = Subject::new();
+        subject
+            .register_executor_with_options(
+                &"move".into(),
+                test_func,
+                ExecutorOptions::new().ports(ports.clone()),
+            )
+            .unwrap();
+        subject
+            .register_executor(&"idle".into(), test_func)
+            .unwrap();
+
+        let handle = subject.get_executor_handle(&"move".into()).unwrap();
+        assert_eq!(subject.executor_ports(&handle), Some(&ports[..]));
+        let handle = subject.get_executor_handle(&"idle".into()).unwrap();
+        assert_eq!(subject.executor_ports(&handle), None);
+    }
+
+    #[test]
+    fn calls_condition_correctly() {
+        fn test_func(_: &Blackboard, (left, right): (i32, i32)) -> bool {
+            left < right
+        }
+
+        let mut subject = Subject::new();
+        subject
+            .register_condition(&Identifier::from("Test"), test_func)
+            .unwrap();
+        let handle = subject
+            .get_condition_handle(&Identifier::from("Test"))
+            .unwrap();
+        assert_eq!(
+            subject.call_condition(&handle, &Blackboard::new(), (1, 2)),
+            Some(true)
+        );
+        assert_eq!(
+            subject.call_condition(&handle, &Blackboard::new(), (2, 1)),
+            Some(false)
+        );
     }
 }
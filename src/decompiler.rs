@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+use crate::{
+    tree::{
+        BehaviourNode, BehaviourTree, ParallelPolicy, CONDITION_ID, COOLDOWN_ID, DECORATOR_ID,
+        DELAY_ID, EXECUTOR_ID, FALLBACK_ID, FORCE_FAILURE_ID, FORCE_SUCCESS_ID, ID_MASK,
+        INVERTER_ID, NODE_SIZE, OPCODE_SHIFT, PARALLEL_FIELD_BITS, PARALLEL_FIELD_MASK,
+        PARALLEL_ID, REACTIVE_FALLBACK_ID, REACTIVE_SEQUENCE_ID, REPEAT_ID, RETRY_UNTIL_SUCCESS_ID,
+        SEQUENCE_ID, TIMEOUT_ID, UNTIL_FAILURE_ID,
+    },
+    verifier::VerificationError,
+};
+
+const VERIFIED: &str = "verified trees only refer to registered entries";
+
+impl<Calltype> BehaviourTree<Calltype> {
+    /// Rebuilds the tree the code was compiled from, looking up the
+    /// identifiers of its executors, decorators and conditions in the context.
+    ///
+    /// The code is verified first, so trees from any source can be given.
+    /// For trees produced by `compile`, compiling the result gives the same
+    /// code; other layouts of the same nodes come back as the same tree but
+    /// recompile to the layout `compile` uses. Parallel policies come back as
+    /// `RequireAll` or `RequireOne` whenever their thresholds match those.
+    pub fn decompile(&self) -> Result<BehaviourNode, VerificationError> {
+        self.verify()?;
+        Ok(BehaviourNode::Root(Box::new(self.decompile_node(0))))
+    }
+
+    fn decompile_node(&self, position: usize) -> BehaviourNode {
+        let word = self.code[position];
+        let operand = word & !ID_MASK;
+        let child_offset = self.code[position + 1] as usize;
+        let children = |count: u32| -> Vec<BehaviourNode> {
+            (0..count as usize)
+                .map(|idx| self.decompile_node(child_offset + idx * NODE_SIZE))
+                .collect()
+        };
+        let child = || Box::new(self.decompile_node(child_offset));
+        let handle = self.handle(position);
+        let duration = Duration::from_millis(operand as u64);
+
+        match (word >> OPCODE_SHIFT) as u8 {
+            SEQUENCE_ID => BehaviourNode::Sequence {
+                children: children(operand),
+            },
+            FALLBACK_ID => BehaviourNode::Fallback {
+                children: children(operand),
+            },
+            REACTIVE_SEQUENCE_ID => BehaviourNode::ReactiveSequence {
+                children: children(operand),
+            },
+            REACTIVE_FALLBACK_ID => BehaviourNode::ReactiveFallback {
+                children: children(operand),
+            },
+            PARALLEL_ID => {
+                let count = operand & PARALLEL_FIELD_MASK;
+                let failure = (operand >> PARALLEL_FIELD_BITS) & PARALLEL_FIELD_MASK;
+                let success = operand >> (PARALLEL_FIELD_BITS * 2);
+                let policy = match (success, failure) {
+                    (success, 1) if success == count => ParallelPolicy::RequireAll,
+                    (1, failure) if failure == count => ParallelPolicy::RequireOne,
+                    (success, failure) => ParallelPolicy::Threshold {
+                        success: success as usize,
+                        failure: failure as usize,
+                    },
+                };
+                BehaviourNode::Parallel {
+                    policy,
+                    children: children(count),
+                }
+            }
+            DECORATOR_ID => BehaviourNode::Decorator {
+                name: self.context.decorator_id(&handle).expect(VERIFIED).clone(),
+                parameters: self.parameters(position).clone(),
+                child: child(),
+            },
+            EXECUTOR_ID => BehaviourNode::Executor(
+                self.context.executor_id(&handle).expect(VERIFIED).clone(),
+                self.parameters(position).clone(),
+            ),
+            CONDITION_ID => BehaviourNode::Condition(
+                self.context.condition_id(&handle).expect(VERIFIED).clone(),
+            ),
+            INVERTER_ID => BehaviourNode::Inverter { child: child() },
+            FORCE_SUCCESS_ID => BehaviourNode::ForceSuccess { child: child() },
+            FORCE_FAILURE_ID => BehaviourNode::ForceFailure { child: child() },
+            REPEAT_ID => BehaviourNode::Repeat {
+                count: operand as usize,
+                child: child(),
+            },
+            RETRY_UNTIL_SUCCESS_ID => BehaviourNode::RetryUntilSuccess {
+                attempts: operand as usize,
+                child: child(),
+            },
+            UNTIL_FAILURE_ID => BehaviourNode::UntilFailure { child: child() },
+            TIMEOUT_ID => BehaviourNode::Timeout {
+                duration,
+                child: child(),
+            },
+            COOLDOWN_ID => BehaviourNode::Cooldown {
+                duration,
+                child: child(),
+            },
+            DELAY_ID => BehaviourNode::Delay {
+                duration,
+                child: child(),
+            },
+            opcode => unreachable!("verified trees have no opcode {opcode}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        blackboard::Blackboard,
+        context::BehaviourContext,
+        parameter::Parameters,
+        state::TreeResult,
+        tree::{BehaviourNode as Node, BehaviourTree, ParallelPolicy},
+        verifier::{VerificationError, VerificationErrorKind},
+    };
+
+    fn succeed(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+        TreeResult::Success
+    }
+
+    fn keep(result: TreeResult, _: &Parameters, _: ()) -> TreeResult {
+        result
+    }
+
+    fn ready(_: &Blackboard, _: ()) -> bool {
+        true
+    }
+
+    fn context() -> Arc<BehaviourContext<()>> {
+        let mut context = BehaviourContext::new();
+        context.register_executor(&"idle".into(), succeed).unwrap();
+        context
+            .register_executor(&"npc:walk".into(), succeed)
+            .unwrap();
+        context.register_decorator(&"keep".into(), keep).unwrap();
+        context.register_condition(&"ready".into(), ready).unwrap();
+        Arc::new(context)
+    }
+
+    #[test]
+    fn round_trips() {
+        let ctx = context();
+        let source = Node::from_dsl(
+            r#"reactive_fallback {
+                reactive_sequence { cond:ready; act:npc:walk(speed = 1.5, to = $target, who = @npc:guard) }
+                parallel(success = 2, failure = 2) {
+                    repeat(3) { force_success { act:idle } }
+                    retry(2) { force_failure { act:idle } }
+                    parallel(one) { until_failure { act:idle }; act:idle }
+                }
+                sequence {
+                    timeout(1500ms) { act:idle }
+                    cooldown(2s) { invert { cond:ready } }
+                    delay(10ms) { decorate:keep(label = "guard") { fallback { act:idle } } }
+                    parallel { act:idle; act:idle }
+                }
+            }"#,
+        )
+        .unwrap();
+        let tree = source.clone().compile(Arc::downgrade(&ctx)).unwrap();
+
+        let decompiled = tree.decompile().unwrap();
+        assert_eq!(decompiled, source);
+        let recompiled = decompiled.compile(Arc::downgrade(&ctx)).unwrap();
+        assert_eq!(recompiled.code(), tree.code());
+        assert_eq!(recompiled.constants(), tree.constants());
+    }
+
+    #[test]
+    fn decompiles_other_layouts() {
+        let ctx = context();
+        let source = Node::from_dsl("sequence { invert { cond:ready }; act:idle }").unwrap();
+        let tree = source.clone().compile(Arc::downgrade(&ctx)).unwrap();
+
+        // Moves the condition, last in breadth first order, ahead of the
+        // sequence's children.
+        let mut moved = source.clone().compile(Arc::downgrade(&ctx)).unwrap();
+        let order = [0, 3, 1, 2];
+        for (node, &from) in order.iter().enumerate() {
+            moved.code[node * 2] = tree.code[from * 2];
+            moved.code[node * 2 + 1] = tree.code[from * 2 + 1];
+            moved.constant_indices[node] = tree.constant_indices[from];
+            moved.handle_generations[node] = tree.handle_generations[from];
+        }
+        moved.code[1] = 4;
+        moved.code[5] = 2;
+
+        let decompiled = moved.decompile().unwrap();
+        assert_eq!(decompiled, source);
+        let recompiled = decompiled.compile(Arc::downgrade(&ctx)).unwrap();
+        assert_eq!(recompiled.code(), tree.code());
+        assert_ne!(recompiled.code(), moved.code());
+    }
+
+    #[test]
+    fn canonical_parallel_policies() {
+        let ctx = context();
+        let parallel = |policy| {
+            Node::Root(Box::new(Node::Parallel {
+                policy,
+                children: vec![
+                    Node::Executor("idle".into(), Parameters::new()),
+                    Node::Executor("idle".into(), Parameters::new()),
+                ],
+            }))
+        };
+        let decompile = |policy| {
+            parallel(policy)
+                .compile(Arc::downgrade(&ctx))
+                .unwrap()
+                .decompile()
+                .unwrap()
+        };
+
+        let all = ParallelPolicy::Threshold {
+            success: 2,
+            failure: 1,
+        };
+        assert_eq!(decompile(all), parallel(ParallelPolicy::RequireAll));
+        let one = ParallelPolicy::Threshold {
+            success: 1,
+            failure: 2,
+        };
+        assert_eq!(decompile(one), parallel(ParallelPolicy::RequireOne));
+    }
+
+    #[test]
+    fn decompiles_loaded_trees() {
+        let ctx = context();
+        let source = Node::from_dsl("sequence { cond:ready; act:npc:walk(speed = 2) }").unwrap();
+        let bytes = source
+            .clone()
+            .compile(Arc::downgrade(&ctx))
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+
+        let loaded = BehaviourTree::from_bytes(&bytes, Arc::downgrade(&ctx)).unwrap();
+        assert_eq!(loaded.decompile(), Ok(source));
+    }
+
+    #[test]
+    fn rejects_unverifiable_trees() {
+        let ctx = context();
+        let mut tree = Node::from_dsl("sequence { cond:ready; act:idle }")
+            .unwrap()
+            .compile(Arc::downgrade(&ctx))
+            .unwrap();
+        tree.code[2] += 1;
+
+        assert_eq!(
+            tree.decompile(),
+            Err(VerificationError {
+                offset: 2,
+                kind: VerificationErrorKind::UnknownCondition(1)
+            })
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    dsl::print_parameters,
+    identifier::Identifier,
+    tree::{
+        opcode_name, BehaviourTree, CONDITION_ID, COOLDOWN_ID, DECORATOR_ID, DELAY_ID, EXECUTOR_ID,
+        FALLBACK_ID, ID_MASK, NODE_SIZE, OPCODE_SHIFT, PARALLEL_FIELD_BITS, PARALLEL_FIELD_MASK,
+        PARALLEL_ID, REACTIVE_FALLBACK_ID, REACTIVE_SEQUENCE_ID, REPEAT_ID, RETRY_UNTIL_SUCCESS_ID,
+        SEQUENCE_ID, TIMEOUT_ID,
+    },
+};
+
+impl<Calltype> BehaviourTree<Calltype> {
+    /// Lists the compiled code one node per line: its offset, its two raw
+    /// words, the opcode name and its decoded operands, with registry handles
+    /// resolved to the identifiers they were registered under and child
+    /// offsets written as `@offset`.
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        for position in (0..self.code.len()).step_by(NODE_SIZE) {
+            out.push_str(&self.disassemble_node(position));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn disassemble_node(&self, position: usize) -> String {
+        let word = self.code[position];
+        let child_offset = self.code.get(position + 1).copied().unwrap_or(0);
+        let opcode = (word >> OPCODE_SHIFT) as u8;
+        let operand = word & !ID_MASK;
+
+        let handle = self.handle(position);
+        let symbol = |id: Option<&Identifier>| match id {
+            Some(id) => format!("{} [#{operand}]", String::from(id.clone())),
+            None => format!("<unregistered #{operand}>"),
+        };
+        let parameters = || {
+            self.constant_indices
+                .get(position / NODE_SIZE)
+                .and_then(|&index| self.constants.get(index as usize))
+                .filter(|parameters| !parameters.is_empty())
+                .map_or_else(String::new, |parameters| {
+                    format!(" {}", print_parameters(parameters))
+                })
+        };
+        let operands = match opcode {
+            SEQUENCE_ID | FALLBACK_ID | REACTIVE_SEQUENCE_ID | REACTIVE_FALLBACK_ID => {
+                format!("children={operand}")
+            }
+            PARALLEL_ID => format!(
+                "success={} failure={} children={}",
+                operand >> (PARALLEL_FIELD_BITS * 2),
+                (operand >> PARALLEL_FIELD_BITS) & PARALLEL_FIELD_MASK,
+                operand & PARALLEL_FIELD_MASK
+            ),
+            EXECUTOR_ID => symbol(self.context.executor_id(&handle)) + &parameters(),
+            DECORATOR_ID => symbol(self.context.decorator_id(&handle)) + &parameters(),
+            CONDITION_ID => symbol(self.context.condition_id(&handle)),
+            REPEAT_ID => format!("count={operand}"),
+            RETRY_UNTIL_SUCCESS_ID => format!("attempts={operand}"),
+            TIMEOUT_ID | COOLDOWN_ID | DELAY_ID => format!("{operand}ms"),
+            _ => String::new(),
+        };
+
+        let name = opcode_name(opcode).map_or_else(|| format!("UNKNOWN({opcode})"), String::from);
+        let mut line = format!("{position:04}  {word:08x} {child_offset:08x}  {name:<19}");
+        for part in [operands, child_offset_text(child_offset)] {
+            if !part.is_empty() {
+                line.push(' ');
+                line.push_str(&part);
+            }
+        }
+        line.trim_end().to_string()
+    }
+}
+
+fn child_offset_text(child_offset: u32) -> String {
+    match child_offset {
+        0 => String::new(),
+        offset => format!("@{offset:04}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        blackboard::Blackboard, context::BehaviourContext, parameter::Parameters,
+        state::TreeResult, tree::BehaviourNode as Node,
+    };
+
+    fn succeed(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+        TreeResult::Success
+    }
+
+    fn keep(result: TreeResult, _: &Parameters, _: ()) -> TreeResult {
+        result
+    }
+
+    fn ready(_: &Blackboard, _: ()) -> bool {
+        true
+    }
+
+    #[test]
+    fn disassembles() {
+        let mut context = BehaviourContext::new();
+        context.register_executor(&"idle".into(), succeed).unwrap();
+        context
+            .register_executor(&"npc:walk".into(), succeed)
+            .unwrap();
+        context.register_decorator(&"keep".into(), keep).unwrap();
+        context.register_condition(&"ready".into(), ready).unwrap();
+        let ctx = Arc::new(context);
+
+        let tree = Node::from_dsl(
+            r#"sequence {
+                cond:ready;
+                parallel(success = 2, failure = 1) {
+                    repeat(3) { act:npc:walk(speed = 1.5, to = $target) }
+                    timeout(1500ms) { act:idle }
+                }
+                decorate:keep(label = "guard") { invert { act:idle } }
+            }"#,
+        )
+        .unwrap()
+        .compile(Arc::downgrade(&ctx))
+        .unwrap();
+
+        let expected = [
+            "0000  01000003 00000002  SEQUENCE            children=3 @0002",
+            "0002  0f000000 00000000  CONDITION           game:ready [#0]",
+            "0004  03020102 00000008  PARALLEL            success=2 failure=1 children=2 @0008",
+            "0006  04000000 0000000c  DECORATOR           game:keep [#0] (label = \"guard\") @0012",
+            "0008  09000003 0000000e  REPEAT              count=3 @0014",
+            "0010  0c0005dc 00000010  TIMEOUT             1500ms @0016",
+            "0012  06000000 00000012  INVERTER            @0018",
+            "0014  05000001 00000000  EXECUTOR            npc:walk [#1] (speed = 1.5, to = $game:target)",
+            "0016  05000000 00000000  EXECUTOR            game:idle [#0]",
+            "0018  05000000 00000000  EXECUTOR            game:idle [#0]",
+        ];
+        assert_eq!(tree.disassemble(), expected.join("\n") + "\n");
+    }
+
+    #[test]
+    fn disassembles_unknown_opcodes() {
+        let mut context = BehaviourContext::new();
+        context.register_executor(&"idle".into(), succeed).unwrap();
+        let ctx = Arc::new(context);
+        let mut tree = Node::Root(Box::new(Node::Executor("idle".into(), Parameters::new())))
+            .compile(Arc::downgrade(&ctx))
+            .unwrap();
+        tree.code[0] = 0x7f00_0001;
+
+        assert_eq!(
+            tree.disassemble(),
+            "0000  7f000001 00000000  UNKNOWN(127)\n"
+        );
+    }
+}
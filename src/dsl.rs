@@ -0,0 +1,869 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::{
+    identifier::Identifier,
+    parameter::{Parameter, Parameters},
+    tree::{BehaviourNode, ParallelPolicy},
+};
+
+const INDENT: &str = "    ";
+
+/// The keywords a node can start with.
+const NODES: &[&str] = &[
+    "sequence",
+    "fallback",
+    "reactive_sequence",
+    "reactive_fallback",
+    "parallel",
+    "act",
+    "cond",
+    "decorate",
+    "invert",
+    "force_success",
+    "force_failure",
+    "repeat",
+    "retry",
+    "until_failure",
+    "timeout",
+    "cooldown",
+    "delay",
+];
+
+/// Why a tree written in the DSL could not be parsed: what the parser
+/// expected at a position and what it found instead. Lines and columns count
+/// from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DslError {
+    line: usize,
+    column: usize,
+    expected: Vec<String>,
+    found: String,
+}
+
+impl DslError {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+}
+
+impl Display for DslError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match self.expected.as_slice() {
+            [] => write!(f, "unexpected {}", self.found),
+            [expected] => write!(f, "expected {expected}, found {}", self.found),
+            expected => write!(
+                f,
+                "expected one of {}, found {}",
+                expected.join(", "),
+                self.found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DslError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Symbol(char),
+    End,
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenKind::Word(word) => write!(f, "`{word}`"),
+            TokenKind::Integer(value) => write!(f, "`{value}`"),
+            TokenKind::Float(value) => write!(f, "`{value:?}`"),
+            TokenKind::Text(_) => write!(f, "a string"),
+            TokenKind::Symbol(symbol) => write!(f, "`{symbol}`"),
+            TokenKind::End => write!(f, "end of input"),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, DslError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+    let mut advance = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let c = chars.next();
+        if c == Some('\n') {
+            line += 1;
+            column = 1;
+        } else if c.is_some() {
+            column += 1;
+        }
+        (c, line, column)
+    };
+    let (mut at_line, mut at_column) = (1, 1);
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (at_line, at_column);
+        let error = |expected: &[&str], found: String| DslError {
+            line: start_line,
+            column: start_column,
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+            found,
+        };
+        let mut text = String::new();
+        let mut take = |chars: &mut std::iter::Peekable<std::str::Chars>, text: &mut String| {
+            let (c, l, col) = advance(chars);
+            (at_line, at_column) = (l, col);
+            text.extend(c);
+            c
+        };
+
+        let kind = if c.is_whitespace() {
+            take(&mut chars, &mut text);
+            continue;
+        } else if c == '/' {
+            take(&mut chars, &mut text);
+            if chars.peek() != Some(&'/') {
+                return Err(error(&[], "`/`".into()));
+            }
+            while chars.peek().is_some_and(|&c| c != '\n') {
+                take(&mut chars, &mut text);
+            }
+            continue;
+        } else if c == '"' {
+            take(&mut chars, &mut text);
+            let mut value = String::new();
+            loop {
+                match take(&mut chars, &mut text) {
+                    Some('"') => break,
+                    Some('\\') => match take(&mut chars, &mut text) {
+                        Some('n') => value.push('\n'),
+                        Some(c @ ('"' | '\\')) => value.push(c),
+                        other => {
+                            let found = other.map_or("end of input".into(), |c| format!("`{c}`"));
+                            return Err(error(&["`n`", "`\"`", "`\\`"], found));
+                        }
+                    },
+                    Some(c) => value.push(c),
+                    None => return Err(error(&["`\"`"], "end of input".into())),
+                }
+            }
+            TokenKind::Text(value)
+        } else if c.is_ascii_digit() || c == '-' {
+            take(&mut chars, &mut text);
+            if text == "-" && chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                // Negative infinity, the only non-finite float with a sign.
+                while chars.peek().is_some_and(|&c| is_word(c)) {
+                    take(&mut chars, &mut text);
+                }
+            }
+            while chars.peek().is_some_and(|&c| {
+                c.is_ascii_digit()
+                    || c == '.'
+                    || c == 'e'
+                    || (matches!(c, '-' | '+') && text.ends_with('e'))
+            }) {
+                take(&mut chars, &mut text);
+            }
+            if let Ok(value) = text.parse() {
+                TokenKind::Integer(value)
+            } else if let Ok(value) = text.parse() {
+                TokenKind::Float(value)
+            } else {
+                return Err(error(&["a number"], format!("`{text}`")));
+            }
+        } else if is_word(c) {
+            while chars.peek().is_some_and(|&c| is_word(c)) {
+                take(&mut chars, &mut text);
+            }
+            TokenKind::Word(text)
+        } else if "{}();:,=$@".contains(c) {
+            take(&mut chars, &mut text);
+            TokenKind::Symbol(c)
+        } else {
+            return Err(error(&[], format!("`{c}`")));
+        };
+        tokens.push(Token {
+            kind,
+            line: start_line,
+            column: start_column,
+        });
+    }
+    tokens.push(Token {
+        kind: TokenKind::End,
+        line: at_line,
+        column: at_column,
+    });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &TokenKind {
+        &self.tokens[self.position].kind
+    }
+
+    fn next(&mut self) {
+        if self.tokens[self.position].kind != TokenKind::End {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, expected: &[&str]) -> DslError {
+        let token = &self.tokens[self.position];
+        DslError {
+            line: token.line,
+            column: token.column,
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+            found: token.kind.to_string(),
+        }
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = *self.peek() == TokenKind::Symbol(symbol);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn symbol(&mut self, symbol: char) -> Result<(), DslError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&[&format!("`{symbol}`")]))
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> Result<String, DslError> {
+        match self.peek().clone() {
+            TokenKind::Word(word) => {
+                self.next();
+                Ok(word)
+            }
+            _ => Err(self.error(&[expected])),
+        }
+    }
+
+    /// A word, or a string standing in for one that is not a plain word.
+    fn name(&mut self, expected: &str) -> Result<String, DslError> {
+        match self.peek().clone() {
+            TokenKind::Text(name) => {
+                self.next();
+                Ok(name)
+            }
+            _ => self.word(expected),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<Identifier, DslError> {
+        let first = self.name("an identifier")?;
+        if self.eat(':') {
+            let id = self.name("an identifier")?;
+            return Ok(format!("{first}:{id}").into());
+        }
+        Ok(first.into())
+    }
+
+    fn count(&mut self) -> Result<usize, DslError> {
+        self.symbol('(')?;
+        let count = match *self.peek() {
+            TokenKind::Integer(count) if count >= 0 => count as usize,
+            _ => return Err(self.error(&["a count"])),
+        };
+        self.next();
+        self.symbol(')')?;
+        Ok(count)
+    }
+
+    fn duration(&mut self) -> Result<Duration, DslError> {
+        self.symbol('(')?;
+        let amount = match *self.peek() {
+            TokenKind::Integer(amount) if amount >= 0 => amount as u64,
+            _ => return Err(self.error(&["a duration"])),
+        };
+        self.next();
+        let duration = match self.peek() {
+            TokenKind::Word(unit) if unit == "ms" => Duration::from_millis(amount),
+            TokenKind::Word(unit) if unit == "s" => Duration::from_secs(amount),
+            _ => return Err(self.error(&["`ms`", "`s`"])),
+        };
+        self.next();
+        self.symbol(')')?;
+        Ok(duration)
+    }
+
+    fn value(&mut self) -> Result<Parameter, DslError> {
+        let value = match self.peek().clone() {
+            TokenKind::Integer(value) => Parameter::Integer(value),
+            TokenKind::Float(value) => Parameter::Float(value),
+            TokenKind::Text(value) => Parameter::Text(value),
+            TokenKind::Word(word) if matches!(word.as_str(), "inf" | "NaN") => {
+                Parameter::Float(word.parse().unwrap())
+            }
+            TokenKind::Symbol('$') => {
+                self.next();
+                return Ok(Parameter::BlackboardKey(self.identifier()?));
+            }
+            TokenKind::Symbol('@') => {
+                self.next();
+                return Ok(Parameter::Identifier(self.identifier()?));
+            }
+            _ => return Err(self.error(&["a number", "a string", "`$`", "`@`"])),
+        };
+        self.next();
+        Ok(value)
+    }
+
+    fn parameters(&mut self) -> Result<Parameters, DslError> {
+        let mut parameters = Parameters::new();
+        if !self.eat('(') {
+            return Ok(parameters);
+        }
+        while !self.eat(')') {
+            let name = self.name("a parameter name")?;
+            self.symbol('=')?;
+            parameters.insert(&name, self.value()?);
+            if !self.eat(',') && *self.peek() != TokenKind::Symbol(')') {
+                return Err(self.error(&["`,`", "`)`"]));
+            }
+        }
+        Ok(parameters)
+    }
+
+    fn policy(&mut self) -> Result<ParallelPolicy, DslError> {
+        if !self.eat('(') {
+            return Ok(ParallelPolicy::RequireAll);
+        }
+        let policy = match self.word("a parallel policy")?.as_str() {
+            "all" => ParallelPolicy::RequireAll,
+            "one" => ParallelPolicy::RequireOne,
+            "success" => {
+                self.symbol('=')?;
+                let success = self.threshold()?;
+                self.symbol(',')?;
+                if self.word("`failure`")? != "failure" {
+                    self.position -= 1;
+                    return Err(self.error(&["`failure`"]));
+                }
+                self.symbol('=')?;
+                ParallelPolicy::Threshold {
+                    success,
+                    failure: self.threshold()?,
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error(&["`all`", "`one`", "`success`"]));
+            }
+        };
+        self.symbol(')')?;
+        Ok(policy)
+    }
+
+    fn threshold(&mut self) -> Result<usize, DslError> {
+        match *self.peek() {
+            TokenKind::Integer(value) if value >= 0 => {
+                self.next();
+                Ok(value as usize)
+            }
+            _ => Err(self.error(&["a count"])),
+        }
+    }
+
+    fn children(&mut self) -> Result<Vec<BehaviourNode>, DslError> {
+        self.symbol('{')?;
+        let mut children = Vec::new();
+        while !self.eat('}') {
+            children.push(self.node()?);
+            self.eat(';');
+        }
+        Ok(children)
+    }
+
+    fn child(&mut self) -> Result<Box<BehaviourNode>, DslError> {
+        self.symbol('{')?;
+        let child = self.node()?;
+        self.eat(';');
+        self.symbol('}')?;
+        Ok(Box::new(child))
+    }
+
+    fn node(&mut self) -> Result<BehaviourNode, DslError> {
+        let keyword = match self.peek() {
+            TokenKind::Word(word) if NODES.contains(&word.as_str()) => word.clone(),
+            _ => {
+                let expected: Vec<String> = NODES.iter().map(|node| format!("`{node}`")).collect();
+                let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+                return Err(self.error(&expected));
+            }
+        };
+        self.next();
+        Ok(match keyword.as_str() {
+            "sequence" => BehaviourNode::Sequence {
+                children: self.children()?,
+            },
+            "fallback" => BehaviourNode::Fallback {
+                children: self.children()?,
+            },
+            "reactive_sequence" => BehaviourNode::ReactiveSequence {
+                children: self.children()?,
+            },
+            "reactive_fallback" => BehaviourNode::ReactiveFallback {
+                children: self.children()?,
+            },
+            "parallel" => BehaviourNode::Parallel {
+                policy: self.policy()?,
+                children: self.children()?,
+            },
+            "act" => {
+                self.symbol(':')?;
+                BehaviourNode::Executor(self.identifier()?, self.parameters()?)
+            }
+            "cond" => {
+                self.symbol(':')?;
+                BehaviourNode::Condition(self.identifier()?)
+            }
+            "decorate" => {
+                self.symbol(':')?;
+                BehaviourNode::Decorator {
+                    name: self.identifier()?,
+                    parameters: self.parameters()?,
+                    child: self.child()?,
+                }
+            }
+            "invert" => BehaviourNode::Inverter {
+                child: self.child()?,
+            },
+            "force_success" => BehaviourNode::ForceSuccess {
+                child: self.child()?,
+            },
+            "force_failure" => BehaviourNode::ForceFailure {
+                child: self.child()?,
+            },
+            "repeat" => BehaviourNode::Repeat {
+                count: self.count()?,
+                child: self.child()?,
+            },
+            "retry" => BehaviourNode::RetryUntilSuccess {
+                attempts: self.count()?,
+                child: self.child()?,
+            },
+            "until_failure" => BehaviourNode::UntilFailure {
+                child: self.child()?,
+            },
+            "timeout" => BehaviourNode::Timeout {
+                duration: self.duration()?,
+                child: self.child()?,
+            },
+            "cooldown" => BehaviourNode::Cooldown {
+                duration: self.duration()?,
+                child: self.child()?,
+            },
+            _ => BehaviourNode::Delay {
+                duration: self.duration()?,
+                child: self.child()?,
+            },
+        })
+    }
+}
+
+impl BehaviourNode {
+    /// Parses a tree written in the DSL, e.g.
+    /// `sequence { cond:has_target; decorate:invert { act:flee } }`, into a
+    /// `Root` node ready to compile.
+    pub fn from_dsl(source: &str) -> Result<Self, DslError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let node = parser.node()?;
+        parser.eat(';');
+        if *parser.peek() != TokenKind::End {
+            return Err(parser.error(&["end of input"]));
+        }
+        Ok(BehaviourNode::Root(Box::new(node)))
+    }
+
+    /// Writes the tree in the DSL, one node per line, such that `from_dsl`
+    /// parses it back into the same tree. `Root` nodes are written as their
+    /// child.
+    pub fn to_dsl(&self) -> String {
+        let mut out = String::new();
+        print(self, 0, &mut out);
+        out
+    }
+}
+
+fn print_duration(duration: &Duration) -> String {
+    match duration.as_millis() {
+        millis if millis % 1000 == 0 => format!("({}s)", millis / 1000),
+        millis => format!("({millis}ms)"),
+    }
+}
+
+fn print_text(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Prints a name as a word where the tokenizer would read it back as one, and
+/// as a string otherwise.
+fn print_name(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && name.chars().all(is_word)
+    {
+        name.into()
+    } else {
+        print_text(name)
+    }
+}
+
+fn print_identifier(id: &Identifier) -> String {
+    format!("{}:{}", print_name(id.scope()), print_name(id.id()))
+}
+
+pub(crate) fn print_parameters(parameters: &Parameters) -> String {
+    if parameters.is_empty() {
+        return String::new();
+    }
+    let values: Vec<String> = parameters
+        .iter()
+        .map(|(name, parameter)| {
+            let value = match parameter {
+                Parameter::Integer(value) => value.to_string(),
+                Parameter::Float(value) => format!("{value:?}"),
+                Parameter::Text(value) => print_text(value),
+                Parameter::Identifier(id) => format!("@{}", print_identifier(id)),
+                Parameter::BlackboardKey(key) => format!("${}", print_identifier(key)),
+            };
+            format!("{} = {value}", print_name(name))
+        })
+        .collect();
+    format!("({})", values.join(", "))
+}
+
+fn print(node: &BehaviourNode, depth: usize, out: &mut String) {
+    let id = print_identifier;
+    let (head, children): (String, Option<Vec<&BehaviourNode>>) = match node {
+        BehaviourNode::Root(child) => return print(child, depth, out),
+        BehaviourNode::Sequence { children } => {
+            ("sequence".into(), Some(children.iter().collect()))
+        }
+        BehaviourNode::Fallback { children } => {
+            ("fallback".into(), Some(children.iter().collect()))
+        }
+        BehaviourNode::ReactiveSequence { children } => {
+            ("reactive_sequence".into(), Some(children.iter().collect()))
+        }
+        BehaviourNode::ReactiveFallback { children } => {
+            ("reactive_fallback".into(), Some(children.iter().collect()))
+        }
+        BehaviourNode::Parallel { policy, children } => {
+            let head = match policy {
+                ParallelPolicy::RequireAll => "parallel".into(),
+                ParallelPolicy::RequireOne => "parallel(one)".into(),
+                ParallelPolicy::Threshold { success, failure } => {
+                    format!("parallel(success = {success}, failure = {failure})")
+                }
+            };
+            (head, Some(children.iter().collect()))
+        }
+        BehaviourNode::Executor(name, parameters) => (
+            format!("act:{}{}", id(name), print_parameters(parameters)),
+            None,
+        ),
+        BehaviourNode::Condition(name) => (format!("cond:{}", id(name)), None),
+        BehaviourNode::Decorator {
+            name,
+            parameters,
+            child,
+        } => (
+            format!("decorate:{}{}", id(name), print_parameters(parameters)),
+            Some(vec![child]),
+        ),
+        BehaviourNode::Inverter { child } => ("invert".into(), Some(vec![child])),
+        BehaviourNode::ForceSuccess { child } => ("force_success".into(), Some(vec![child])),
+        BehaviourNode::ForceFailure { child } => ("force_failure".into(), Some(vec![child])),
+        BehaviourNode::Repeat { count, child } => (format!("repeat({count})"), Some(vec![child])),
+        BehaviourNode::RetryUntilSuccess { attempts, child } => {
+            (format!("retry({attempts})"), Some(vec![child]))
+        }
+        BehaviourNode::UntilFailure { child } => ("until_failure".into(), Some(vec![child])),
+        BehaviourNode::Timeout { duration, child } => (
+            format!("timeout{}", print_duration(duration)),
+            Some(vec![child]),
+        ),
+        BehaviourNode::Cooldown { duration, child } => (
+            format!("cooldown{}", print_duration(duration)),
+            Some(vec![child]),
+        ),
+        BehaviourNode::Delay { duration, child } => (
+            format!("delay{}", print_duration(duration)),
+            Some(vec![child]),
+        ),
+    };
+
+    let indent = INDENT.repeat(depth);
+    match children {
+        None => out.push_str(&format!("{indent}{head};\n")),
+        Some(children) => {
+            out.push_str(&format!("{indent}{head} {{\n"));
+            for child in children {
+                print(child, depth + 1, out);
+            }
+            out.push_str(&format!("{indent}}}\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        parameter::{Parameter, Parameters},
+        tree::{BehaviourNode as Node, ParallelPolicy},
+    };
+
+    fn error(source: &str) -> (usize, usize, Vec<String>, String) {
+        let error = Node::from_dsl(source).unwrap_err();
+        (
+            error.line(),
+            error.column(),
+            error.expected().to_vec(),
+            error.found().into(),
+        )
+    }
+
+    #[test]
+    fn parses() {
+        let subject = Node::from_dsl("sequence { cond:has_target; decorate:invert { act:flee } }");
+        assert_eq!(
+            subject,
+            Ok(Node::Root(Box::new(Node::Sequence {
+                children: vec![
+                    Node::Condition("has_target".into()),
+                    Node::Decorator {
+                        name: "invert".into(),
+                        parameters: Parameters::new(),
+                        child: Box::new(Node::Executor("flee".into(), Parameters::new())),
+                    },
+                ],
+            })))
+        );
+    }
+
+    #[test]
+    fn parses_everything() {
+        let source = r#"
+            // Chase the player while they are visible, otherwise patrol.
+            reactive_fallback {
+                reactive_sequence {
+                    cond:npc:sees_player
+                    timeout(2s) { act:npc:chase(speed = 2.5, target = $player) }
+                }
+                parallel(success = 1, failure = 2) {
+                    repeat(3) { act:npc:patrol(route = "north \"gate\"", laps = -1) }
+                    retry(2) { until_failure { act:npc:look(at = @npc:door) } }
+                    parallel(one) { cooldown(1500ms) { invert { cond:heard } } }
+                }
+                force_success { delay(0s) { force_failure { fallback { } } } };
+            }
+        "#;
+        let expected = Node::Root(Box::new(Node::ReactiveFallback {
+            children: vec![
+                Node::ReactiveSequence {
+                    children: vec![
+                        Node::Condition("npc:sees_player".into()),
+                        Node::Timeout {
+                            duration: Duration::from_secs(2),
+                            child: Box::new(Node::Executor(
+                                "npc:chase".into(),
+                                Parameters::new()
+                                    .with("speed", 2.5)
+                                    .with("target", Parameter::BlackboardKey("player".into())),
+                            )),
+                        },
+                    ],
+                },
+                Node::Parallel {
+                    policy: ParallelPolicy::Threshold {
+                        success: 1,
+                        failure: 2,
+                    },
+                    children: vec![
+                        Node::Repeat {
+                            count: 3,
+                            child: Box::new(Node::Executor(
+                                "npc:patrol".into(),
+                                Parameters::new()
+                                    .with("route", "north \"gate\"")
+                                    .with("laps", -1),
+                            )),
+                        },
+                        Node::RetryUntilSuccess {
+                            attempts: 2,
+                            child: Box::new(Node::UntilFailure {
+                                child: Box::new(Node::Executor(
+                                    "npc:look".into(),
+                                    Parameters::new()
+                                        .with("at", Parameter::Identifier("npc:door".into())),
+                                )),
+                            }),
+                        },
+                        Node::Parallel {
+                            policy: ParallelPolicy::RequireOne,
+                            children: vec![Node::Cooldown {
+                                duration: Duration::from_millis(1500),
+                                child: Box::new(Node::Inverter {
+                                    child: Box::new(Node::Condition("heard".into())),
+                                }),
+                            }],
+                        },
+                    ],
+                },
+                Node::ForceSuccess {
+                    child: Box::new(Node::Delay {
+                        duration: Duration::ZERO,
+                        child: Box::new(Node::ForceFailure {
+                            child: Box::new(Node::Fallback {
+                                children: Vec::new(),
+                            }),
+                        }),
+                    }),
+                },
+            ],
+        }));
+        let subject = Node::from_dsl(source).unwrap();
+        assert_eq!(subject, expected);
+        assert_eq!(Node::from_dsl(&subject.to_dsl()), Ok(subject));
+    }
+
+    #[test]
+    fn round_trips_floats() {
+        let values = [
+            1e-7,
+            -2.5e-12,
+            f64::MIN_POSITIVE,
+            1e16,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for value in values {
+            let subject = Node::Root(Box::new(Node::Executor(
+                "a".into(),
+                Parameters::new().with("x", value),
+            )));
+            assert_eq!(Node::from_dsl(&subject.to_dsl()), Ok(subject));
+        }
+
+        let parsed = Node::from_dsl("act:a(x = 0.0000001, y = 1e+3, z = NaN)").unwrap();
+        let Node::Root(executor) = Node::from_dsl(&parsed.to_dsl()).unwrap() else {
+            unreachable!()
+        };
+        let Node::Executor(_, parameters) = *executor else {
+            unreachable!()
+        };
+        assert_eq!(parameters.float("x"), Some(1e-7));
+        assert_eq!(parameters.float("y"), Some(1000.0));
+        assert!(parameters.float("z").is_some_and(f64::is_nan));
+    }
+
+    #[test]
+    fn round_trips_names() {
+        let subject = Node::Root(Box::new(Node::Decorator {
+            name: "my-mod:log.v2".into(),
+            parameters: Parameters::new()
+                .with("level-name", Parameter::BlackboardKey("2fast:key".into()))
+                .with("who", Parameter::Identifier("npc:\"guard\"".into())),
+            child: Box::new(Node::Condition("1st".into())),
+        }));
+
+        let printed = subject.to_dsl();
+        assert!(printed.starts_with(
+            "decorate:\"my-mod\":\"log.v2\"(\"level-name\" = $\"2fast\":key, who = @npc:\"\\\"guard\\\"\")"
+        ));
+        assert_eq!(Node::from_dsl(&printed), Ok(subject));
+    }
+
+    #[test]
+    fn prints() {
+        let subject = Node::from_dsl("sequence { cond:has_target; decorate:invert { act:flee } }");
+        assert_eq!(
+            subject.unwrap().to_dsl(),
+            "sequence {\n    cond:game:has_target;\n    decorate:game:invert {\n        act:game:flee;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let nodes = super::NODES.iter().map(|n| format!("`{n}`")).collect();
+        assert_eq!(
+            error("sequence {\n    cond:a;\n    sequense { }\n}"),
+            (3, 5, nodes, "`sequense`".into())
+        );
+        assert_eq!(
+            error("act:walk(speed 2)"),
+            (1, 16, vec!["`=`".into()], "`2`".into())
+        );
+        assert_eq!(
+            error("timeout(5) { act:a }"),
+            (1, 10, vec!["`ms`".into(), "`s`".into()], "`)`".into())
+        );
+        assert_eq!(
+            error("invert {\n  act:a\n"),
+            (3, 1, vec!["`}`".into()], "end of input".into())
+        );
+        assert_eq!(
+            error("act:a(line = \"open)"),
+            (1, 14, vec!["`\"`".into()], "end of input".into())
+        );
+        assert_eq!(
+            error("act:a act:b"),
+            (1, 7, vec!["end of input".into()], "`act`".into())
+        );
+        assert_eq!(
+            Node::from_dsl("repeat(x) { act:a }")
+                .unwrap_err()
+                .to_string(),
+            "1:8: expected a count, found `x`"
+        );
+        assert_eq!(
+            Node::from_dsl("act:a % 2").unwrap_err().to_string(),
+            "1:7: unexpected `%`"
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt::Display;
 
-const DEFAULT_NAMESPACE: &'static str = "game";
-const DIVIDER: &'static str = ":";
-const DEFAULT_ID: &'static str = "unknown";
+const DEFAULT_NAMESPACE: &str = "game";
+const DIVIDER: &str = ":";
+const DEFAULT_ID: &str = "unknown";
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(from = "String"))]
 pub struct Identifier {
     scope: String,
     id: String,
@@ -29,11 +30,15 @@ impl From<String> for Identifier {
         };
         if cleaned.contains(DIVIDER) {
             let split: Vec<&str> = cleaned.split(DIVIDER).collect();
-            let scope: String = (*split.get(0).unwrap()).into();
+            let scope: String = (*split.first().unwrap()).into();
             let id: String = split[1..].join("_");
             Self {
                 scope,
-                id: if !id.eq("") { id } else { DEFAULT_ID.into() },
+                id: if !id.is_empty() {
+                    id
+                } else {
+                    DEFAULT_ID.into()
+                },
             }
         } else {
             Self {
@@ -50,9 +55,9 @@ impl From<&str> for Identifier {
     }
 }
 
-impl Into<String> for Identifier {
-    fn into(self) -> String {
-        self.scope + &String::from(":") + &self.id
+impl From<Identifier> for String {
+    fn from(value: Identifier) -> Self {
+        value.scope + DIVIDER + &value.id
     }
 }
 
@@ -77,7 +82,7 @@ mod tests {
     fn from() {
         let scope = "scope";
         let id = "id";
-        let identifier = Identifier::from(vec![scope, DIVIDER.into(), id].concat());
+        let identifier = Identifier::from([scope, DIVIDER, id].concat());
 
         assert_eq!(identifier.scope, scope);
         assert_eq!(identifier.id, id);
@@ -95,7 +100,7 @@ mod tests {
     #[test]
     fn from_dirty_no_scope() {
         let id = "id";
-        let identifier = Identifier::from(vec![DIVIDER, id].concat());
+        let identifier = Identifier::from([DIVIDER, id].concat());
 
         assert_eq!(identifier.scope, DEFAULT_NAMESPACE);
         assert_eq!(identifier.id, id);
@@ -104,7 +109,7 @@ mod tests {
     #[test]
     fn from_no_id() {
         let scope = "scope";
-        let identifier = Identifier::from(vec![scope, DIVIDER].concat());
+        let identifier = Identifier::from([scope, DIVIDER].concat());
 
         assert_eq!(identifier.scope, scope);
         assert_eq!(identifier.id, DEFAULT_ID);
@@ -1,13 +1,20 @@
-#![feature(tuple_trait)]
-#![feature(unboxed_closures)]
-#![feature(fn_traits)]
-#![feature(box_into_inner)]
-#![feature(is_some_and)]
+pub mod blackboard;
+pub mod bytecode;
+pub mod clock;
 pub mod context;
+pub mod decompiler;
+pub mod disassembler;
+pub mod dsl;
 pub mod identifier;
+#[cfg(feature = "serde")]
+pub mod load;
+pub mod parameter;
 pub mod registry;
 pub mod state;
 pub mod tree;
+pub mod verifier;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
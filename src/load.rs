@@ -0,0 +1,208 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::tree::BehaviourNode;
+
+/// Why a tree description could not be loaded, and where in the source the
+/// problem was found. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl LoadError {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<ron::error::SpannedError> for LoadError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self {
+            line: error.span.start.line,
+            column: error.span.start.col,
+            message: error.code.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(error: serde_json::Error) -> Self {
+        // serde_json appends the location to its messages, which is reported
+        // separately here.
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        Self {
+            line: error.line(),
+            column: error.column(),
+            message: message.strip_suffix(&suffix).unwrap_or(&message).into(),
+        }
+    }
+}
+
+impl BehaviourNode {
+    /// Loads a tree written in RON, e.g.
+    /// `Root(Sequence(children: [Executor("game:walk", {})]))`.
+    pub fn from_ron(source: &str) -> Result<Self, LoadError> {
+        Ok(ron::from_str(source)?)
+    }
+
+    /// Loads a tree written in JSON, with each node an object keyed by its
+    /// variant, e.g. `{"Root": {"Executor": ["game:walk", {}]}}`.
+    pub fn from_json(source: &str) -> Result<Self, LoadError> {
+        Ok(serde_json::from_str(source)?)
+    }
+}
+
+/// Deserialises a duration given as a whole number of milliseconds.
+pub(crate) fn millis<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        parameter::{Parameter, Parameters},
+        tree::{BehaviourNode as Node, ParallelPolicy},
+    };
+
+    fn expected() -> Node {
+        Node::Root(Box::new(Node::Sequence {
+            children: vec![
+                Node::Condition("npc:sees_player".into()),
+                Node::Parallel {
+                    policy: ParallelPolicy::Threshold {
+                        success: 1,
+                        failure: 2,
+                    },
+                    children: vec![
+                        Node::Timeout {
+                            duration: Duration::from_millis(1500),
+                            child: Box::new(Node::Executor(
+                                "npc:chase".into(),
+                                Parameters::new()
+                                    .with("speed", 2.5)
+                                    .with("target", Parameter::BlackboardKey("player".into())),
+                            )),
+                        },
+                        Node::Decorator {
+                            name: "npc:log".into(),
+                            parameters: Parameters::new(),
+                            child: Box::new(Node::Executor(
+                                "npc:shout".into(),
+                                Parameters::new().with("line", "halt!"),
+                            )),
+                        },
+                    ],
+                },
+            ],
+        }))
+    }
+
+    #[test]
+    fn from_ron() {
+        let source = r#"
+            Root(Sequence(children: [
+                Condition("npc:sees_player"),
+                Parallel(
+                    policy: Threshold(success: 1, failure: 2),
+                    children: [
+                        Timeout(
+                            duration: 1500,
+                            child: Executor("npc:chase", {
+                                "speed": Float(2.5),
+                                "target": BlackboardKey("player"),
+                            }),
+                        ),
+                        Decorator(
+                            name: "npc:log",
+                            child: Executor("npc:shout", { "line": Text("halt!") }),
+                        ),
+                    ],
+                ),
+            ]))
+        "#;
+        assert_eq!(Node::from_ron(source), Ok(expected()));
+    }
+
+    #[test]
+    fn from_json() {
+        let source = r#"
+            {"Root": {"Sequence": {"children": [
+                {"Condition": "npc:sees_player"},
+                {"Parallel": {
+                    "policy": {"Threshold": {"success": 1, "failure": 2}},
+                    "children": [
+                        {"Timeout": {
+                            "duration": 1500,
+                            "child": {"Executor": ["npc:chase", {
+                                "speed": {"Float": 2.5},
+                                "target": {"BlackboardKey": "player"}
+                            }]}
+                        }},
+                        {"Decorator": {
+                            "name": "npc:log",
+                            "child": {"Executor": ["npc:shout", {"line": {"Text": "halt!"}}]}
+                        }}
+                    ]
+                }}
+            ]}}}
+        "#;
+        assert_eq!(Node::from_json(source), Ok(expected()));
+    }
+
+    #[test]
+    fn default_policy() {
+        let subject = Node::from_ron("Parallel(children: [])").unwrap();
+        assert_eq!(
+            subject,
+            Node::Parallel {
+                policy: ParallelPolicy::RequireAll,
+                children: Vec::new()
+            }
+        );
+    }
+
+    #[test]
+    fn ron_error_location() {
+        let source = "Root(Sequence(children: [\n    Executer(\"walk\", {}),\n]))";
+        let error = Node::from_ron(source).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 5));
+        assert!(error.message().contains("Executer"));
+        assert!(error.to_string().starts_with("2:5: "));
+    }
+
+    #[test]
+    fn json_error_location() {
+        let source = "{\"Root\": {\"Repeat\": {\n  \"count\": \"twice\",\n  \"child\": {\"Condition\": \"x\"}}}}";
+        let error = Node::from_json(source).unwrap_err();
+        assert_eq!(error.line(), 2);
+        assert!(error.message().contains("invalid type"));
+        assert!(!error.message().contains("at line"));
+    }
+}
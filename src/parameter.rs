@@ -0,0 +1,324 @@
+use crate::identifier::Identifier;
+
+/// A constant argument given to a node where it appears in a tree.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub enum Parameter {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Identifier(Identifier),
+    /// The blackboard entry the node should read from or write to.
+    BlackboardKey(Identifier),
+}
+
+impl Parameter {
+    /// The type of value the parameter holds, or `None` for a blackboard key,
+    /// whose value is only known once the tree runs.
+    pub fn port_type(&self) -> Option<PortType> {
+        match self {
+            Self::Integer(_) => Some(PortType::Integer),
+            Self::Float(_) => Some(PortType::Float),
+            Self::Text(_) => Some(PortType::Text),
+            Self::Identifier(_) => Some(PortType::Identifier),
+            Self::BlackboardKey(_) => None,
+        }
+    }
+}
+
+// Floats hash by their bits, with both zeroes hashing alike as they compare
+// equal. NaNs never compare equal, so how they hash does not matter.
+impl std::hash::Hash for Parameter {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Integer(value) => value.hash(state),
+            Self::Float(value) => {
+                let value = if *value == 0.0 { 0.0 } else { *value };
+                value.to_bits().hash(state)
+            }
+            Self::Text(value) => value.hash(state),
+            Self::Identifier(value) | Self::BlackboardKey(value) => value.hash(state),
+        }
+    }
+}
+
+impl From<i64> for Parameter {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<f64> for Parameter {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for Parameter {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Parameter {
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl From<Identifier> for Parameter {
+    fn from(value: Identifier) -> Self {
+        Self::Identifier(value)
+    }
+}
+
+/// The named parameters of a node.
+#[derive(Debug, Clone, PartialEq, Default, Hash)]
+pub struct Parameters {
+    entries: Vec<(String, Parameter)>,
+}
+
+impl Parameters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a parameter, replacing any existing one with the same name.
+    pub fn with(mut self, name: &str, value: impl Into<Parameter>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: &str, value: impl Into<Parameter>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(key, _)| key == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((name.into(), value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn integer(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            Parameter::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            Parameter::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            Parameter::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn identifier(&self, name: &str) -> Option<&Identifier> {
+        match self.get(name)? {
+            Parameter::Identifier(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn blackboard_key(&self, name: &str) -> Option<&Identifier> {
+        match self.get(name)? {
+            Parameter::BlackboardKey(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Parameter)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Deserialised from a map of names to parameters, keeping their order.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Parameters {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Parameters;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of parameter names to parameters")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Parameters, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut parameters = Parameters::new();
+                while let Some((name, value)) = map.next_entry::<String, Parameter>()? {
+                    parameters.insert(&name, value);
+                }
+                Ok(parameters)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+/// The type of value passed through a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortType {
+    Integer,
+    Float,
+    Text,
+    Identifier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    /// Read by the node, given either as a constant or a blackboard key.
+    Input,
+    /// Written by the node, so it must be given a blackboard key.
+    Output,
+    /// Read and written by the node through a blackboard key.
+    InOut,
+}
+
+/// A parameter an executor declares it expects, checked when trees using the
+/// executor are compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Port {
+    name: String,
+    direction: PortDirection,
+    port_type: PortType,
+}
+
+impl Port {
+    pub fn new(name: &str, direction: PortDirection, port_type: PortType) -> Self {
+        Self {
+            name: name.into(),
+            direction,
+            port_type,
+        }
+    }
+
+    pub fn input(name: &str, port_type: PortType) -> Self {
+        Self::new(name, PortDirection::Input, port_type)
+    }
+
+    pub fn output(name: &str, port_type: PortType) -> Self {
+        Self::new(name, PortDirection::Output, port_type)
+    }
+
+    pub fn in_out(name: &str, port_type: PortType) -> Self {
+        Self::new(name, PortDirection::InOut, port_type)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn direction(&self) -> PortDirection {
+        self.direction
+    }
+
+    pub fn port_type(&self) -> PortType {
+        self.port_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasher;
+
+    use super::{Parameter, Parameters, PortType};
+    use crate::identifier::Identifier;
+
+    #[test]
+    fn typed_access() {
+        let subject = Parameters::new()
+            .with("count", 3)
+            .with("speed", 1.5)
+            .with("animation", "walk")
+            .with("target", Identifier::from("npc:guard"))
+            .with("output", Parameter::BlackboardKey("position".into()));
+
+        assert_eq!(subject.len(), 5);
+        assert_eq!(subject.integer("count"), Some(3));
+        assert_eq!(subject.float("speed"), Some(1.5));
+        assert_eq!(subject.text("animation"), Some("walk"));
+        assert_eq!(subject.identifier("target"), Some(&"npc:guard".into()));
+        assert_eq!(subject.blackboard_key("output"), Some(&"position".into()));
+    }
+
+    #[test]
+    fn port_types() {
+        assert_eq!(Parameter::from(3).port_type(), Some(PortType::Integer));
+        assert_eq!(Parameter::from(0.5).port_type(), Some(PortType::Float));
+        assert_eq!(Parameter::from("walk").port_type(), Some(PortType::Text));
+        assert_eq!(
+            Parameter::from(Identifier::from("npc")).port_type(),
+            Some(PortType::Identifier)
+        );
+        assert_eq!(Parameter::BlackboardKey("x".into()).port_type(), None);
+    }
+
+    #[test]
+    fn mistyped_or_missing() {
+        let subject = Parameters::new().with("count", 3);
+
+        assert_eq!(subject.float("count"), None);
+        assert_eq!(subject.text("count"), None);
+        assert_eq!(subject.integer("missing"), None);
+    }
+
+    #[test]
+    fn insert_replaces() {
+        let mut subject = Parameters::new().with("count", 3);
+        subject.insert("count", 4);
+
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.get("count"), Some(&Parameter::Integer(4)));
+        assert_eq!(
+            subject.iter().collect::<Vec<_>>(),
+            vec![("count", &Parameter::Integer(4))]
+        );
+    }
+
+    #[test]
+    fn hashes_like_equality() {
+        let hasher = std::collections::hash_map::RandomState::new();
+        let hash = |parameters: &Parameters| hasher.hash_one(parameters);
+        let zero = Parameters::new().with("speed", 0.0).with("to", "door");
+        let negative_zero = Parameters::new().with("speed", -0.0).with("to", "door");
+
+        assert_eq!(zero, negative_zero);
+        assert_eq!(hash(&zero), hash(&negative_zero));
+        assert_ne!(
+            hash(&Parameters::new().with("key", Identifier::from("x"))),
+            hash(&Parameters::new().with("key", Parameter::BlackboardKey("x".into())))
+        );
+    }
+}
@@ -1,7 +1,16 @@
+use std::collections::HashMap;
+
 pub use crate::identifier::Identifier;
 pub struct Registry<T> {
     keys: Vec<Identifier>,
-    values: Vec<T>,
+    values: Vec<Option<T>>,
+    /// How many times each slot has been emptied or overwritten, so handles to
+    /// the entries it held before can be told apart from the current one.
+    generations: Vec<u32>,
+    /// The index of each key in `keys`, so lookups by identifier need not scan.
+    indices: HashMap<Identifier, usize>,
+    /// Slots emptied by `remove` or `clear`, reused by later inserts.
+    free: Vec<usize>,
 }
 
 impl<T> Registry<T> {
@@ -9,6 +18,9 @@ impl<T> Registry<T> {
         Self {
             keys: Vec::new(),
             values: Vec::new(),
+            generations: Vec::new(),
+            indices: HashMap::new(),
+            free: Vec::new(),
         }
     }
 
@@ -16,44 +28,125 @@ impl<T> Registry<T> {
         Self {
             keys: Vec::with_capacity(capacity),
             values: Vec::with_capacity(capacity),
+            generations: Vec::with_capacity(capacity),
+            indices: HashMap::with_capacity(capacity),
+            free: Vec::new(),
         }
     }
 
     pub fn contains(&self, id: &Identifier) -> bool {
-        self.keys.contains(&id)
+        self.indices.contains_key(id)
     }
 
     pub fn get_handle(&self, id: &Identifier) -> Option<RegistryHandle> {
-        for (idx, key) in self.keys.iter().enumerate() {
-            if id == key {
-                return Some(RegistryHandle::new(idx));
-            }
+        self.indices
+            .get(id)
+            .map(|&idx| RegistryHandle::with_generation(idx, self.generations[idx]))
+    }
+
+    /// The slot a handle refers to, if the entry it was issued for is still
+    /// there.
+    fn slot(&self, handle: &RegistryHandle) -> Option<usize> {
+        match self.values.get(handle.idx) {
+            Some(Some(_)) if self.generations[handle.idx] == handle.generation => Some(handle.idx),
+            _ => None,
         }
-        None
     }
 
+    /// The entry a handle refers to, or `None` if it has since been removed or
+    /// replaced.
     pub fn get(&self, handle: &RegistryHandle) -> Option<&T> {
-        self.values.get(handle.idx)
+        self.values[self.slot(handle)?].as_ref()
+    }
+
+    /// The identifier an entry was registered under.
+    pub fn get_key(&self, handle: &RegistryHandle) -> Option<&Identifier> {
+        Some(&self.keys[self.slot(handle)?])
     }
 
     pub fn get_direct(&self, id: &Identifier) -> Option<&T> {
-        let handle = self.get_handle(&id)?;
+        let handle = self.get_handle(id)?;
         self.get(&handle)
     }
 
     pub fn insert(&mut self, id: &Identifier, value: T) -> Result<(), RegistryInsertError> {
-        if !self.contains(&id) {
-            self.keys.push(id.clone());
-            self.values.push(value);
-            Ok(())
-        } else {
-            Err(RegistryInsertError::EntryAlreadyExists)
+        if self.contains(id) {
+            return Err(RegistryInsertError::EntryAlreadyExists);
         }
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.keys[idx] = id.clone();
+                self.values[idx] = Some(value);
+                idx
+            }
+            None => {
+                self.keys.push(id.clone());
+                self.values.push(Some(value));
+                self.generations.push(0);
+                self.keys.len() - 1
+            }
+        };
+        self.indices.insert(id.clone(), idx);
+        Ok(())
+    }
+
+    /// Swaps the entry registered under `id` for `value`, returning the old
+    /// one. Handles to the old entry become stale, as whatever was checked
+    /// against it may not hold for its replacement.
+    pub fn replace(&mut self, id: &Identifier, value: T) -> Result<T, RegistryReplaceError> {
+        let idx = *self
+            .indices
+            .get(id)
+            .ok_or(RegistryReplaceError::EntryMissing)?;
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+        Ok(self.values[idx].replace(value).unwrap())
+    }
+
+    /// Like `replace`, but builds the new entry from the old one.
+    pub fn replace_with(
+        &mut self,
+        id: &Identifier,
+        replacement: impl FnOnce(T) -> T,
+    ) -> Result<(), RegistryReplaceError> {
+        let idx = *self
+            .indices
+            .get(id)
+            .ok_or(RegistryReplaceError::EntryMissing)?;
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+        let old = self.values[idx].take().unwrap();
+        self.values[idx] = Some(replacement(old));
+        Ok(())
+    }
+
+    /// Removes the entry registered under `id`, making its handles stale.
+    pub fn remove(&mut self, id: &Identifier) -> Option<T> {
+        let idx = self.indices.remove(id)?;
+        self.generations[idx] = self.generations[idx].wrapping_add(1);
+        self.free.push(idx);
+        self.values[idx].take()
     }
 
+    /// Removes every entry, making all handles issued so far stale.
     pub fn clear(&mut self) {
-        self.keys.clear();
-        self.values.clear();
+        for idx in self.indices.drain().map(|(_, idx)| idx) {
+            self.generations[idx] = self.generations[idx].wrapping_add(1);
+            self.values[idx] = None;
+            self.free.push(idx);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Registry<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(key, value)| value.as_ref().map(|_| key))
+            .collect();
+        f.debug_struct("Registry")
+            .field("keys", &keys)
+            .finish_non_exhaustive()
     }
 }
 
@@ -63,19 +156,32 @@ impl<T> Default for Registry<T> {
     }
 }
 
+/// Refers to an entry in a `Registry` by its slot, and the generation of that
+/// slot when the entry was inserted, so the handle goes stale once the entry is
+/// removed or replaced rather than referring to whatever takes its slot.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct RegistryHandle {
     idx: usize,
+    generation: u32,
 }
 
 impl RegistryHandle {
+    /// A handle to the first generation of the entry at `idx`.
     pub fn new(idx: usize) -> Self {
-        Self { idx }
+        Self::with_generation(idx, 0)
+    }
+
+    pub fn with_generation(idx: usize, generation: u32) -> Self {
+        Self { idx, generation }
     }
 
     pub fn value(&self) -> usize {
         self.idx
     }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -83,9 +189,14 @@ pub enum RegistryInsertError {
     EntryAlreadyExists,
 }
 
+#[derive(Debug, PartialEq)]
+pub enum RegistryReplaceError {
+    EntryMissing,
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Registry, RegistryHandle, RegistryInsertError};
+    use super::{Registry, RegistryHandle, RegistryInsertError, RegistryReplaceError};
     pub use crate::identifier::Identifier;
 
     type Subject = Registry<usize>;
@@ -98,6 +209,7 @@ mod tests {
             let subject = Subject::default();
             assert_eq!(subject.keys.capacity(), 0);
             assert_eq!(subject.values.capacity(), 0);
+            assert_eq!(subject.generations.capacity(), 0);
         }
 
         #[test]
@@ -106,6 +218,8 @@ mod tests {
             let subject = Subject::with_capacity(capacity);
             assert_eq!(subject.keys.capacity(), capacity);
             assert_eq!(subject.values.capacity(), capacity);
+            assert_eq!(subject.generations.capacity(), capacity);
+            assert!(subject.indices.capacity() >= capacity);
         }
     }
 
@@ -116,8 +230,7 @@ mod tests {
         pub fn works() {
             let mut subject = Subject::default();
             let id = Identifier::from("test");
-            subject.keys.push(id.clone());
-            subject.values.push(13);
+            subject.insert(&id, 13).unwrap();
 
             assert!(subject.contains(&id));
         }
@@ -138,12 +251,29 @@ mod tests {
         pub fn works() {
             let mut subject = Subject::default();
             let id = Identifier::from("test");
-            subject.keys.push(id.clone());
-            subject.values.push(13);
+            subject.insert(&id, 13).unwrap();
 
             assert_eq!(subject.get_handle(&id), Some(RegistryHandle::new(0)));
         }
 
+        #[test]
+        pub fn stable() {
+            let mut subject = Subject::default();
+            let first = Identifier::from("first");
+            subject.insert(&first, 1).unwrap();
+            for idx in 0..100 {
+                subject
+                    .insert(&Identifier::from(idx.to_string()), idx)
+                    .unwrap();
+            }
+
+            assert_eq!(subject.get_handle(&first), Some(RegistryHandle::new(0)));
+            assert_eq!(
+                subject.get_handle(&Identifier::from("99")),
+                Some(RegistryHandle::new(100))
+            );
+        }
+
         #[test]
         pub fn out_of_bounds() {
             let subject = Subject::default();
@@ -160,8 +290,7 @@ mod tests {
         pub fn works() {
             let mut subject = Subject::default();
             let value: usize = 12;
-            subject.keys.push(Identifier::from("test"));
-            subject.values.push(value);
+            subject.insert(&Identifier::from("test"), value).unwrap();
             let handle = RegistryHandle::new(0);
 
             assert_eq!(subject.get(&handle), Some(&value));
@@ -176,6 +305,26 @@ mod tests {
         }
     }
 
+    mod get_key {
+        use super::*;
+
+        #[test]
+        pub fn works() {
+            let mut subject = Subject::default();
+            let id = Identifier::from("test");
+            subject.insert(&id, 12).unwrap();
+
+            assert_eq!(subject.get_key(&RegistryHandle::new(0)), Some(&id));
+        }
+
+        #[test]
+        pub fn out_of_bounds() {
+            let subject = Subject::default();
+
+            assert_eq!(subject.get_key(&RegistryHandle::new(0)), None);
+        }
+    }
+
     mod insert {
         use super::*;
 
@@ -187,8 +336,8 @@ mod tests {
 
             assert_eq!(subject.insert(&id, value), Ok(()));
 
-            assert_eq!(subject.keys.get(0), Some(&id));
-            assert_eq!(subject.values.get(0), Some(&value));
+            assert_eq!(subject.keys.first(), Some(&id));
+            assert_eq!(subject.values.first(), Some(&Some(value)));
         }
 
         #[test]
@@ -197,16 +346,123 @@ mod tests {
             let id = Identifier::from("test");
             let existing_value: usize = 1;
             let value: usize = 12;
-            subject.keys.push(id.clone());
-            subject.values.push(existing_value);
+            subject.insert(&id, existing_value).unwrap();
 
             assert_eq!(
                 subject.insert(&id, value),
                 Err(RegistryInsertError::EntryAlreadyExists)
             );
 
-            assert_eq!(subject.keys.get(0), Some(&id));
-            assert_eq!(subject.values.get(0), Some(&existing_value));
+            assert_eq!(subject.keys.first(), Some(&id));
+            assert_eq!(subject.values.first(), Some(&Some(existing_value)));
+        }
+    }
+
+    mod replace {
+        use super::*;
+
+        #[test]
+        pub fn works() {
+            let mut subject = Subject::default();
+            let id = Identifier::from("test");
+            subject.insert(&id, 1).unwrap();
+            let old = subject.get_handle(&id).unwrap();
+
+            assert_eq!(subject.replace(&id, 2), Ok(1));
+
+            let new = subject.get_handle(&id).unwrap();
+            assert_eq!(new.value(), old.value());
+            assert_eq!(subject.get(&new), Some(&2));
+            assert_eq!(subject.get(&old), None);
+            assert_eq!(subject.get_key(&old), None);
+        }
+
+        #[test]
+        pub fn missing() {
+            let mut subject = Subject::default();
+
+            assert_eq!(
+                subject.replace(&Identifier::from("test"), 2),
+                Err(RegistryReplaceError::EntryMissing)
+            );
+            assert!(!subject.contains(&Identifier::from("test")));
+        }
+    }
+
+    mod replace_with {
+        use super::*;
+
+        #[test]
+        pub fn works() {
+            let mut subject = Subject::default();
+            let id = Identifier::from("test");
+            subject.insert(&id, 1).unwrap();
+            let old = subject.get_handle(&id).unwrap();
+
+            assert_eq!(subject.replace_with(&id, |value| value + 1), Ok(()));
+
+            assert_eq!(subject.get_direct(&id), Some(&2));
+            assert_eq!(subject.get(&old), None);
+            assert_eq!(
+                subject.replace_with(&Identifier::from("missing"), |value| value),
+                Err(RegistryReplaceError::EntryMissing)
+            );
+        }
+    }
+
+    mod remove {
+        use super::*;
+
+        #[test]
+        pub fn works() {
+            let mut subject = Subject::default();
+            let id = Identifier::from("test");
+            subject.insert(&id, 1).unwrap();
+            let handle = subject.get_handle(&id).unwrap();
+
+            assert_eq!(subject.remove(&id), Some(1));
+
+            assert!(!subject.contains(&id));
+            assert_eq!(subject.get_handle(&id), None);
+            assert_eq!(subject.get(&handle), None);
+            assert_eq!(subject.get_key(&handle), None);
+            assert_eq!(subject.remove(&id), None);
+        }
+
+        #[test]
+        pub fn reuses_slots() {
+            let mut subject = Subject::default();
+            let removed = Identifier::from("removed");
+            let added = Identifier::from("added");
+            subject.insert(&removed, 1).unwrap();
+            let stale = subject.get_handle(&removed).unwrap();
+            subject.remove(&removed);
+
+            subject.insert(&added, 2).unwrap();
+
+            let handle = subject.get_handle(&added).unwrap();
+            assert_eq!(handle, RegistryHandle::with_generation(0, 1));
+            assert_eq!(subject.get(&handle), Some(&2));
+            assert_eq!(subject.get(&stale), None);
+        }
+    }
+
+    mod clear {
+        use super::*;
+
+        #[test]
+        pub fn makes_handles_stale() {
+            let mut subject = Subject::default();
+            let id = Identifier::from("test");
+            subject.insert(&id, 1).unwrap();
+            let stale = subject.get_handle(&id).unwrap();
+
+            subject.clear();
+            assert!(!subject.contains(&id));
+            subject.insert(&id, 2).unwrap();
+
+            assert_eq!(subject.get(&stale), None);
+            assert_eq!(subject.get_direct(&id), Some(&2));
         }
     }
 }
@@ -1,15 +1,277 @@
-#[derive(Debug, PartialEq, Eq)]
+use std::time::Duration;
+
+use crate::{blackboard::Blackboard, tree::BehaviourTree};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TreeResult {
     Failure,
     Success,
     Running,
 }
 
+/// Runtime state of a single agent driven by a shared `BehaviourTree`.
+///
+/// All data that changes while ticking lives here, so one compiled tree can
+/// drive any number of agents.
+#[derive(Debug)]
 pub struct TreeState {
     executions: Vec<ExecutionState>,
+    resumed: Vec<ExecutionState>,
+    path: Vec<usize>,
+    nodes: Vec<NodeState>,
+    now: Duration,
+    blackboard: Blackboard,
 }
 
+impl TreeState {
+    pub fn new<CallType>(tree: &BehaviourTree<CallType>) -> Self {
+        Self {
+            executions: Vec::new(),
+            resumed: Vec::new(),
+            path: Vec::new(),
+            nodes: vec![NodeState::default(); tree.node_count()],
+            now: Duration::ZERO,
+            blackboard: Blackboard::new(),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The branches that were left running by the last tick.
+    pub fn executions(&self) -> &Vec<ExecutionState> {
+        &self.executions
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    pub fn blackboard_mut(&mut self) -> &mut Blackboard {
+        &mut self.blackboard
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.executions.is_empty()
+    }
+
+    /// Resets all running state. The blackboard is left untouched.
+    pub fn clear(&mut self) {
+        self.executions.clear();
+        self.resumed.clear();
+        self.path.clear();
+        self.nodes.fill(NodeState::default());
+    }
+
+    pub(crate) fn node_mut(&mut self, node: usize) -> &mut NodeState {
+        &mut self.nodes[node]
+    }
+
+    pub(crate) fn begin_tick(&mut self, now: Duration) {
+        self.resumed = std::mem::take(&mut self.executions);
+        self.path.clear();
+        self.now = now;
+    }
+
+    /// The clock's time at the start of the current tick.
+    pub(crate) fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Finishes the current tick, returning the positions of the running nodes
+    /// that were not resumed and have been abandoned.
+    pub(crate) fn end_tick(&mut self) -> Vec<usize> {
+        self.resumed
+            .drain(..)
+            .map(|execution| execution.position)
+            .collect()
+    }
+
+    /// Stops every running branch, returning the positions of the running nodes.
+    pub(crate) fn take_executions(&mut self) -> Vec<usize> {
+        self.executions
+            .drain(..)
+            .map(|execution| execution.position)
+            .collect()
+    }
+
+    pub(crate) fn enter(&mut self, position: usize) {
+        self.path.push(position);
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    /// Records the node at `position` as running so the next tick resumes there.
+    pub(crate) fn suspend(&mut self, position: usize) {
+        self.executions.push(ExecutionState {
+            previous: self.path.clone(),
+            position,
+        });
+    }
+
+    /// Marks the suspended execution at `position` as resumed.
+    pub(crate) fn resume(&mut self, position: usize) {
+        self.resumed
+            .retain(|execution| execution.position != position);
+    }
+
+    /// Drops every execution suspended beneath the node at `position`, whether
+    /// suspended by this tick or still waiting to be resumed, returning the
+    /// positions of the running nodes.
+    pub(crate) fn abandon(&mut self, position: usize) -> Vec<usize> {
+        let mut abandoned = Vec::new();
+        for executions in [&mut self.executions, &mut self.resumed] {
+            executions.retain(|execution| {
+                let beneath = execution.previous.contains(&position);
+                if beneath {
+                    abandoned.push(execution.position);
+                }
+                !beneath
+            });
+        }
+        abandoned
+    }
+
+    /// Whether the node at `position`, or one of its descendants, was left
+    /// running and has not been resumed yet.
+    pub(crate) fn is_resumed(&self, position: usize) -> bool {
+        self.resumed.iter().any(|execution| {
+            execution.position == position || execution.previous.contains(&position)
+        })
+    }
+
+    /// The children of the node at `position` that were left running.
+    pub(crate) fn resumed_children(&self, position: usize) -> Vec<usize> {
+        let mut children: Vec<usize> = self
+            .resumed
+            .iter()
+            .filter_map(|execution| execution.child_of(position))
+            .collect();
+        children.sort_unstable();
+        children.dedup();
+        children
+    }
+}
+
+/// Memory of a single node, indexed by the node's position in the code.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct NodeState {
+    /// Results of the children of a parallel node, for the current run.
+    pub(crate) statuses: Vec<TreeResult>,
+    /// Completed runs of the child of a repeating decorator.
+    pub(crate) counter: usize,
+    /// When a time based decorator started, or for cooldowns when it is ready.
+    pub(crate) timestamp: Duration,
+}
+
+/// A running branch: the position of the running node and the positions of
+/// its ancestors, starting at the root.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ExecutionState {
     previous: Vec<usize>,
     position: usize,
 }
+
+impl ExecutionState {
+    pub fn previous(&self) -> &Vec<usize> {
+        &self.previous
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn child_of(&self, position: usize) -> Option<usize> {
+        let idx = self.previous.iter().position(|&node| node == position)?;
+        Some(*self.previous.get(idx + 1).unwrap_or(&self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Blackboard, ExecutionState, NodeState, TreeState};
+
+    fn state(node_count: usize) -> TreeState {
+        TreeState {
+            executions: Vec::new(),
+            resumed: Vec::new(),
+            path: Vec::new(),
+            nodes: vec![NodeState::default(); node_count],
+            now: Duration::ZERO,
+            blackboard: Blackboard::new(),
+        }
+    }
+
+    #[test]
+    fn suspend_records_path() {
+        let mut subject = state(5);
+        subject.enter(0);
+        subject.enter(4);
+        subject.suspend(8);
+        subject.leave();
+        subject.leave();
+
+        assert_eq!(
+            subject.executions(),
+            &vec![ExecutionState {
+                previous: vec![0, 4],
+                position: 8
+            }]
+        );
+    }
+
+    #[test]
+    fn resumed_children() {
+        let mut subject = state(5);
+        subject.enter(0);
+        subject.suspend(2);
+        subject.enter(4);
+        subject.suspend(8);
+        subject.begin_tick(Duration::ZERO);
+
+        assert!(!subject.is_running());
+        assert!(subject.is_resumed(4));
+        assert!(subject.is_resumed(8));
+        assert!(!subject.is_resumed(6));
+        assert_eq!(subject.resumed_children(0), vec![2, 4]);
+        assert_eq!(subject.resumed_children(4), vec![8]);
+        assert_eq!(subject.resumed_children(8), Vec::<usize>::new());
+
+        subject.resume(8);
+        assert_eq!(subject.resumed_children(0), vec![2]);
+    }
+
+    #[test]
+    fn abandon() {
+        let mut subject = state(5);
+        subject.enter(0);
+        subject.suspend(2);
+        subject.enter(4);
+        subject.suspend(8);
+        assert_eq!(subject.abandon(4), vec![8]);
+
+        assert_eq!(subject.executions().len(), 1);
+        assert_eq!(subject.executions()[0].position(), 2);
+    }
+
+    #[test]
+    fn clear() {
+        let mut subject = state(2);
+        subject.enter(0);
+        subject.suspend(2);
+        subject
+            .node_mut(1)
+            .statuses
+            .push(super::TreeResult::Running);
+        subject.clear();
+
+        assert!(!subject.is_running());
+        assert_eq!(subject.node_count(), 2);
+        assert!(subject.node_mut(1).statuses.is_empty());
+    }
+}
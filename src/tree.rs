@@ -1,19 +1,106 @@
-use std::{marker::Tuple, rc::Rc};
-use std::rc::Weak;
+use std::collections::{hash_map::RandomState, HashMap, VecDeque};
+use std::hash::BuildHasher;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-use crate::{context::BehaviourContext, registry::Identifier};
+use crate::{
+    clock::{Clock, SystemClock},
+    context::BehaviourContext,
+    parameter::{Parameter, Parameters, Port, PortDirection, PortType},
+    registry::{Identifier, RegistryHandle},
+    state::{TreeResult, TreeState},
+    verifier::MAX_DEPTH,
+};
 
-type VecType = u32;
-const NODE_SIZE: usize = (u64::BITS / VecType::BITS) as usize;
-const ID_MASK: u32 = 0xF000;
+/// Trees are valid when built and keep their context from changing, see
+/// `BehaviourTree`.
+const VALID_HANDLES: &str = "trees only hold handles to registered entries";
 
+pub(crate) type VecType = u32;
+pub(crate) const NODE_SIZE: usize = (u64::BITS / VecType::BITS) as usize;
+pub(crate) const OPCODE_SHIFT: u32 = 24;
+pub(crate) const ID_MASK: u32 = 0xFF00_0000;
 
-const SEQUENCE_ID: u8 = 1;
-const FALLBACK_ID: u8 = 2;
-const PARALLEL_ID: u8 = 3;
-const DECORATOR_ID: u8 = 4;
-const EXECUTOR_ID: u8 = 5;
+/// The version of the opcode table below, stored with saved bytecode. Bump it
+/// whenever an opcode is added, removed or changes how it is encoded.
+pub(crate) const OPCODE_VERSION: u16 = 1;
 
+pub(crate) const SEQUENCE_ID: u8 = 1;
+pub(crate) const FALLBACK_ID: u8 = 2;
+pub(crate) const PARALLEL_ID: u8 = 3;
+pub(crate) const DECORATOR_ID: u8 = 4;
+pub(crate) const EXECUTOR_ID: u8 = 5;
+pub(crate) const INVERTER_ID: u8 = 6;
+pub(crate) const FORCE_SUCCESS_ID: u8 = 7;
+pub(crate) const FORCE_FAILURE_ID: u8 = 8;
+pub(crate) const REPEAT_ID: u8 = 9;
+pub(crate) const RETRY_UNTIL_SUCCESS_ID: u8 = 10;
+pub(crate) const UNTIL_FAILURE_ID: u8 = 11;
+pub(crate) const TIMEOUT_ID: u8 = 12;
+pub(crate) const COOLDOWN_ID: u8 = 13;
+pub(crate) const DELAY_ID: u8 = 14;
+pub(crate) const CONDITION_ID: u8 = 15;
+pub(crate) const REACTIVE_SEQUENCE_ID: u8 = 16;
+pub(crate) const REACTIVE_FALLBACK_ID: u8 = 17;
+
+/// The name of an opcode, or `None` if it is not one.
+pub(crate) fn opcode_name(opcode: u8) -> Option<&'static str> {
+    Some(match opcode {
+        SEQUENCE_ID => "SEQUENCE",
+        FALLBACK_ID => "FALLBACK",
+        PARALLEL_ID => "PARALLEL",
+        DECORATOR_ID => "DECORATOR",
+        EXECUTOR_ID => "EXECUTOR",
+        INVERTER_ID => "INVERTER",
+        FORCE_SUCCESS_ID => "FORCE_SUCCESS",
+        FORCE_FAILURE_ID => "FORCE_FAILURE",
+        REPEAT_ID => "REPEAT",
+        RETRY_UNTIL_SUCCESS_ID => "RETRY_UNTIL_SUCCESS",
+        UNTIL_FAILURE_ID => "UNTIL_FAILURE",
+        TIMEOUT_ID => "TIMEOUT",
+        COOLDOWN_ID => "COOLDOWN",
+        DELAY_ID => "DELAY",
+        CONDITION_ID => "CONDITION",
+        REACTIVE_SEQUENCE_ID => "REACTIVE_SEQUENCE",
+        REACTIVE_FALLBACK_ID => "REACTIVE_FALLBACK",
+        _ => return None,
+    })
+}
+
+pub(crate) const PARALLEL_FIELD_BITS: u32 = 8;
+pub(crate) const PARALLEL_FIELD_MASK: u32 = 0xFF;
+
+/// Decides when a parallel node completes, based on how many of its children
+/// have succeeded or failed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub enum ParallelPolicy {
+    /// Succeeds once every child succeeds, fails as soon as one fails.
+    #[default]
+    RequireAll,
+    /// Succeeds as soon as one child succeeds, fails once every child fails.
+    RequireOne,
+    /// Succeeds once `success` children succeed, fails once `failure` children
+    /// fail or enough have failed that `success` can no longer be reached.
+    Threshold { success: usize, failure: usize },
+}
+
+impl ParallelPolicy {
+    fn thresholds(&self, child_count: usize) -> (usize, usize) {
+        match *self {
+            ParallelPolicy::RequireAll => (child_count, 1),
+            ParallelPolicy::RequireOne => (1, child_count),
+            ParallelPolicy::Threshold { success, failure } => (success, failure),
+        }
+    }
+}
+
+/// A node of a behaviour tree before compilation.
+///
+/// With the `serde` feature, trees can be loaded from RON or JSON using
+/// `from_ron` and `from_json`, with durations given in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub enum BehaviourNode {
     Root(Box<BehaviourNode>),
     Sequence {
@@ -22,18 +109,84 @@ pub enum BehaviourNode {
     Fallback {
         children: Vec<BehaviourNode>,
     },
+    /// Like `Sequence`, but ticks every child from the first on each tick so a
+    /// running child is abandoned as soon as an earlier child fails.
+    ReactiveSequence {
+        children: Vec<BehaviourNode>,
+    },
+    /// Like `Fallback`, but ticks every child from the first on each tick so a
+    /// running child is abandoned as soon as an earlier child succeeds.
+    ReactiveFallback {
+        children: Vec<BehaviourNode>,
+    },
     Parallel {
+        #[cfg_attr(feature = "serde", serde(default))]
+        policy: ParallelPolicy,
         children: Vec<BehaviourNode>,
     },
+    /// Runs its child and passes the result through a registered decorator,
+    /// along with `parameters`.
     Decorator {
         name: Identifier,
+        #[cfg_attr(feature = "serde", serde(default))]
+        parameters: Parameters,
+        child: Box<BehaviourNode>,
+    },
+    /// Runs a registered executor, passing it `parameters`.
+    Executor(Identifier, Parameters),
+    /// Succeeds or fails depending on a registered condition, never running.
+    Condition(Identifier),
+    /// Swaps the success and failure of its child.
+    Inverter {
+        child: Box<BehaviourNode>,
+    },
+    /// Succeeds once its child completes, whatever the child's result.
+    ForceSuccess {
+        child: Box<BehaviourNode>,
+    },
+    /// Fails once its child completes, whatever the child's result.
+    ForceFailure {
+        child: Box<BehaviourNode>,
+    },
+    /// Runs its child until it has succeeded `count` times, failing as soon as
+    /// the child fails.
+    Repeat {
+        count: usize,
+        child: Box<BehaviourNode>,
+    },
+    /// Runs its child until it succeeds, failing once the child has failed
+    /// `attempts` times.
+    RetryUntilSuccess {
+        attempts: usize,
+        child: Box<BehaviourNode>,
+    },
+    /// Keeps running its child, restarting it on every success, until it fails.
+    UntilFailure {
+        child: Box<BehaviourNode>,
+    },
+    /// Fails if its child is still running once `duration` has passed.
+    Timeout {
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::load::millis"))]
+        duration: Duration,
+        child: Box<BehaviourNode>,
+    },
+    /// Fails without running its child until `duration` has passed since the
+    /// child last completed.
+    Cooldown {
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::load::millis"))]
+        duration: Duration,
+        child: Box<BehaviourNode>,
+    },
+    /// Stays running for `duration` before running its child.
+    Delay {
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::load::millis"))]
+        duration: Duration,
         child: Box<BehaviourNode>,
     },
-    Executor(Identifier),
 }
 
 impl BehaviourNode {
-    pub fn compile<Calltype: Tuple>(
+    pub fn compile<Calltype>(
         self,
         ctx: Weak<BehaviourContext<Calltype>>,
     ) -> Result<BehaviourTree<Calltype>, TreeCompilationError> {
@@ -42,123 +195,147 @@ impl BehaviourNode {
             _ => Err(TreeCompilationError::InitialNonRootNode),
         }
     }
-    fn compile_inner<Calltype: Tuple>(
+    fn compile_inner<Calltype>(
         self,
         context: Weak<BehaviourContext<Calltype>>,
     ) -> Result<BehaviourTree<Calltype>, TreeCompilationError> {
-        let ctx_wrapped = context.upgrade();
-        if ctx_wrapped.is_none() {
-            return Err(TreeCompilationError::NonExistentContext);
-        }
-        let ctx = ctx_wrapped.unwrap();
-        let mut nodes = Vec::new();
+        let ctx = context
+            .upgrade()
+            .ok_or(TreeCompilationError::NonExistentContext)?;
+        // Nodes are laid out breadth first so that the children of every
+        // composite end up contiguous in `code`, starting at its child offset.
+        let mut nodes = VecDeque::new();
         let mut code = Vec::new();
         let mut node_offset: usize = 0;
         let mut node_count = 0;
-        nodes.push(self);
-
-        while nodes.len() > 0 {
-            let node = nodes.pop().unwrap();
+        // Parameters are pooled, with every node referring to its set by index
+        // and nodes without parameters sharing the empty set at index 0.
+        let mut constants = vec![Parameters::new()];
+        let mut constant_indices = Vec::new();
+        // The pool indices of the sets with each hash, so finding a duplicate
+        // only compares against sets that may be equal.
+        let hasher = RandomState::new();
+        let mut pooled: HashMap<u64, Vec<usize>> =
+            HashMap::from([(hasher.hash_one(&constants[0]), vec![0])]);
+        let mut handle_generations = Vec::new();
+        // The type each blackboard key is used as by the ports mapped to it.
+        let mut key_types = HashMap::new();
+        nodes.push_back((self, 1));
 
-            match node {
+        while let Some((node, depth)) = nodes.pop_front() {
+            if depth > MAX_DEPTH {
+                return Err(TreeCompilationError::TooDeep { limit: MAX_DEPTH });
+            }
+            let mut parameters = Parameters::new();
+            let mut generation = 0;
+            let (word, children) = match node {
                 Self::Root(_) => return Err(TreeCompilationError::RootNodeInTree),
-                Self::Sequence { mut children } => {
-                    let child_count = children.len() as u32;
-                    if children.len() > 0 {
-                        if child_count & !ID_MASK != child_count {
-                            return Err(TreeCompilationError::TooManyChildNodes);
-                        }
-                        code.push(((SEQUENCE_ID as VecType) << 24) | child_count);
-                        node_offset += NODE_SIZE;
-
-                        let child_offset = (node_offset + nodes.len() * NODE_SIZE) as u32;
-                        code.push(child_offset);
-
-                        nodes.append(&mut children);
-
-                        node_count += 1;
-                    }
+                Self::Sequence { children } => (
+                    Self::encode_composite(SEQUENCE_ID, children.len())?,
+                    children,
+                ),
+                Self::Fallback { children } => (
+                    Self::encode_composite(FALLBACK_ID, children.len())?,
+                    children,
+                ),
+                Self::ReactiveSequence { children } => (
+                    Self::encode_composite(REACTIVE_SEQUENCE_ID, children.len())?,
+                    children,
+                ),
+                Self::ReactiveFallback { children } => (
+                    Self::encode_composite(REACTIVE_FALLBACK_ID, children.len())?,
+                    children,
+                ),
+                Self::Parallel { policy, children } => {
+                    (Self::encode_parallel(policy, children.len())?, children)
                 }
-                Self::Fallback { mut children } => {
-                    let child_count = children.len() as u32;
-                    if children.len() > 0 {
-                        if child_count & !ID_MASK != child_count {
-                            return Err(TreeCompilationError::TooManyChildNodes);
-                        }
-                        code.push(((FALLBACK_ID as VecType) << 24) | child_count);
-                        node_offset += NODE_SIZE;
-
-                        let child_offset = (node_offset + nodes.len() * NODE_SIZE) as u32;
-                        code.push(child_offset);
-
-                        nodes.append(&mut children);
-
-                        nodes.append(&mut children);
-
-                        node_count += 1;
+                Self::Decorator {
+                    name,
+                    parameters: node_parameters,
+                    child,
+                } => match ctx.get_decorator_handle(&name) {
+                    Some(handle) => {
+                        parameters = node_parameters;
+                        generation = handle.generation();
+                        (
+                            Self::encode_handle(DECORATOR_ID, name, handle)?,
+                            vec![*child],
+                        )
                     }
-                }
-                Self::Parallel { mut children } => {
-                    let child_count = children.len() as u32;
-                    if children.len() > 0 {
-                        if child_count & !ID_MASK != child_count {
-                            return Err(TreeCompilationError::TooManyChildNodes);
+                    None => return Err(TreeCompilationError::UnknownDecorator(name)),
+                },
+                Self::Executor(id, node_parameters) => match ctx.get_executor_handle(&id) {
+                    Some(handle) => {
+                        if let Some(ports) = ctx.executor_ports(&handle) {
+                            Self::check_ports(&id, ports, &node_parameters, &mut key_types)?;
                         }
-                        code.push(((PARALLEL_ID as VecType) << 24) | child_count);
-                        node_offset += NODE_SIZE;
-
-                        let child_offset = (node_offset + nodes.len() * NODE_SIZE) as u32;
-                        code.push(child_offset);
-
-                        nodes.append(&mut children);
-
-                        node_count += 1;
+                        parameters = node_parameters;
+                        generation = handle.generation();
+                        (Self::encode_handle(EXECUTOR_ID, id, handle)?, Vec::new())
+                    }
+                    None => return Err(TreeCompilationError::UnknownExecutor(id)),
+                },
+                Self::Condition(id) => match ctx.get_condition_handle(&id) {
+                    Some(handle) => {
+                        generation = handle.generation();
+                        (Self::encode_handle(CONDITION_ID, id, handle)?, Vec::new())
                     }
+                    None => return Err(TreeCompilationError::UnknownCondition(id)),
+                },
+                Self::Inverter { child } => (Self::encode(INVERTER_ID, 0), vec![*child]),
+                Self::ForceSuccess { child } => (Self::encode(FORCE_SUCCESS_ID, 0), vec![*child]),
+                Self::ForceFailure { child } => (Self::encode(FORCE_FAILURE_ID, 0), vec![*child]),
+                Self::Repeat { count, child } => {
+                    (Self::encode_count(REPEAT_ID, count)?, vec![*child])
                 }
-                Self::Decorator { name, child } => {
-                    if let Some(handle) = ctx.get_decorator_handle(&name) {
-                        let handle_value = handle.value();
-                        let masked_handle = (handle_value as u32) & !ID_MASK;
-                        if masked_handle as usize != handle_value {
-                            return Err(TreeCompilationError::UnencodableRegistryHandle { id: name, registry_index: handle_value});
-                        }
-                        node_offset += NODE_SIZE;
-                        
-                        code.push(masked_handle | ((DECORATOR_ID as u32) << 24));
+                Self::RetryUntilSuccess { attempts, child } => (
+                    Self::encode_count(RETRY_UNTIL_SUCCESS_ID, attempts)?,
+                    vec![*child],
+                ),
+                Self::UntilFailure { child } => (Self::encode(UNTIL_FAILURE_ID, 0), vec![*child]),
+                Self::Timeout { duration, child } => {
+                    (Self::encode_duration(TIMEOUT_ID, duration)?, vec![*child])
+                }
+                Self::Cooldown { duration, child } => {
+                    (Self::encode_duration(COOLDOWN_ID, duration)?, vec![*child])
+                }
+                Self::Delay { duration, child } => {
+                    (Self::encode_duration(DELAY_ID, duration)?, vec![*child])
+                }
+            };
+            node_offset += NODE_SIZE;
+            code.push(word);
 
-                        let child_offset = (node_offset + nodes.len() * NODE_SIZE) as u32;
-                        println!("CHILD_OFFSET: {:?}", child_offset);
-                        code.push(child_offset);
+            // Leaf nodes have no children, so their second word is left empty.
+            let child_offset = if children.is_empty() {
+                0
+            } else {
+                (node_offset + nodes.len() * NODE_SIZE) as u32
+            };
+            code.push(child_offset);
 
-                        nodes.push(Box::into_inner(child));
+            nodes.extend(children.into_iter().map(|child| (child, depth + 1)));
 
-                        node_count += 1;
-                    } else {
-                        return Err(TreeCompilationError::UnknownDecorator(name));
-                    }
-                }
-                Self::Executor(id) => {
-                    if let Some(handle) = ctx.get_executor_handle(&id) {
-                        let handle_value = handle.value();
-                        let masked_handle = (handle_value as u32) & !ID_MASK;
-                        if masked_handle as usize != handle_value {
-                            return Err(TreeCompilationError::UnencodableRegistryHandle { id, registry_index: handle_value});
-                        }
-                        node_offset += NODE_SIZE;
-                        
-                        code.push(masked_handle | ((EXECUTOR_ID as u32) << 24));
-                        code.push(0);
-
-                        node_count += 1;
-                    } else {
-                        return Err(TreeCompilationError::UnknownExecutor(id));
-                    }
+            let candidates = pooled.entry(hasher.hash_one(&parameters)).or_default();
+            let constant_index = match candidates.iter().find(|&&c| constants[c] == parameters) {
+                Some(&index) => index,
+                None => {
+                    candidates.push(constants.len());
+                    constants.push(parameters);
+                    constants.len() - 1
                 }
-            }
+            };
+            constant_indices.push(constant_index as u32);
+            handle_generations.push(generation);
+
+            node_count += 1;
         }
         if node_count > 0 {
             Ok(BehaviourTree {
                 code,
+                constants,
+                constant_indices,
+                handle_generations,
                 context: ctx,
                 node_count,
             })
@@ -166,6 +343,140 @@ impl BehaviourNode {
             Err(TreeCompilationError::NoNodes)
         }
     }
+
+    /// Checks that a node gives a parameter of the right kind for each port
+    /// its executor declares, and no others.
+    pub(crate) fn check_ports(
+        id: &Identifier,
+        ports: &[Port],
+        parameters: &Parameters,
+        key_types: &mut HashMap<Identifier, PortType>,
+    ) -> Result<(), TreeCompilationError> {
+        let node = || id.clone();
+        if let Some((name, _)) = parameters
+            .iter()
+            .find(|(name, _)| !ports.iter().any(|port| port.name() == *name))
+        {
+            return Err(TreeCompilationError::UnknownPort {
+                node: node(),
+                port: name.into(),
+            });
+        }
+
+        for port in ports {
+            let Some(parameter) = parameters.get(port.name()) else {
+                return Err(TreeCompilationError::MissingPort {
+                    node: node(),
+                    port: port.name().into(),
+                });
+            };
+            match (parameter, port.direction()) {
+                (Parameter::BlackboardKey(key), _) => {
+                    let expected = *key_types.entry(key.clone()).or_insert(port.port_type());
+                    if expected != port.port_type() {
+                        return Err(TreeCompilationError::ConflictingBlackboardKey {
+                            key: key.clone(),
+                            expected,
+                            found: port.port_type(),
+                        });
+                    }
+                }
+                (_, PortDirection::Output | PortDirection::InOut) => {
+                    return Err(TreeCompilationError::UnmappedPort {
+                        node: node(),
+                        port: port.name().into(),
+                    });
+                }
+                (constant, PortDirection::Input) => {
+                    if constant.port_type() != Some(port.port_type()) {
+                        return Err(TreeCompilationError::MistypedPort {
+                            node: node(),
+                            port: port.name().into(),
+                            expected: port.port_type(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn encode(id: u8, operand: u32) -> VecType {
+        ((id as VecType) << OPCODE_SHIFT) | operand
+    }
+
+    fn encode_handle(
+        id: u8,
+        name: Identifier,
+        handle: RegistryHandle,
+    ) -> Result<VecType, TreeCompilationError> {
+        let handle_value = handle.value();
+        let masked_handle = (handle_value as u32) & !ID_MASK;
+        if masked_handle as usize != handle_value {
+            return Err(TreeCompilationError::UnencodableRegistryHandle {
+                id: name,
+                registry_index: handle_value,
+            });
+        }
+        Ok(Self::encode(id, masked_handle))
+    }
+
+    fn encode_count(id: u8, count: usize) -> Result<VecType, TreeCompilationError> {
+        let masked_count = (count as u32) & !ID_MASK;
+        if count == 0 || masked_count as usize != count {
+            return Err(TreeCompilationError::InvalidCount(count));
+        }
+        Ok(Self::encode(id, masked_count))
+    }
+
+    /// Durations are stored in whole milliseconds.
+    fn encode_duration(id: u8, duration: Duration) -> Result<VecType, TreeCompilationError> {
+        let millis = duration.as_millis();
+        let masked_millis = (millis as u32) & !ID_MASK;
+        if masked_millis as u128 != millis {
+            return Err(TreeCompilationError::InvalidDuration(duration));
+        }
+        Ok(Self::encode(id, masked_millis))
+    }
+
+    fn encode_composite(id: u8, child_count: usize) -> Result<VecType, TreeCompilationError> {
+        if child_count == 0 {
+            return Err(TreeCompilationError::NoNodes);
+        }
+        let count = child_count as u32;
+        if count as usize != child_count || count & !ID_MASK != count {
+            return Err(TreeCompilationError::TooManyChildNodes);
+        }
+        Ok(Self::encode(id, count))
+    }
+
+    /// Parallel nodes pack their success threshold, failure threshold and child
+    /// count into one byte each.
+    fn encode_parallel(
+        policy: ParallelPolicy,
+        child_count: usize,
+    ) -> Result<VecType, TreeCompilationError> {
+        if child_count == 0 {
+            return Err(TreeCompilationError::NoNodes);
+        }
+        if child_count > PARALLEL_FIELD_MASK as usize {
+            return Err(TreeCompilationError::TooManyChildNodes);
+        }
+        let (success, failure) = policy.thresholds(child_count);
+        if !(1..=child_count).contains(&success) || !(1..=child_count).contains(&failure) {
+            return Err(TreeCompilationError::InvalidParallelPolicy(policy));
+        }
+        Ok(Self::encode(
+            PARALLEL_ID,
+            ((success as VecType) << (PARALLEL_FIELD_BITS * 2))
+                | ((failure as VecType) << PARALLEL_FIELD_BITS)
+                | child_count as VecType,
+        ))
+    }
+}
+
+fn millis(operand: u32) -> Duration {
+    Duration::from_millis(operand as u64)
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -175,157 +486,608 @@ pub enum TreeCompilationError {
     RootNodeInTree,
     UnknownDecorator(Identifier),
     UnknownExecutor(Identifier),
-    UnencodableRegistryHandle{id: Identifier, registry_index: usize},
+    UnknownCondition(Identifier),
+    UnencodableRegistryHandle {
+        id: Identifier,
+        registry_index: usize,
+    },
     TooManyChildNodes,
+    /// The tree has more than `limit` levels of nodes, not counting `Root`.
+    TooDeep {
+        limit: usize,
+    },
+    InvalidParallelPolicy(ParallelPolicy),
+    InvalidCount(usize),
+    InvalidDuration(Duration),
     NonExistentContext,
+    /// An executor node lacks a parameter for one of its declared ports.
+    MissingPort {
+        node: Identifier,
+        port: String,
+    },
+    /// An executor node has a parameter its executor declares no port for.
+    UnknownPort {
+        node: Identifier,
+        port: String,
+    },
+    /// A constant given for an input port is of the wrong type.
+    MistypedPort {
+        node: Identifier,
+        port: String,
+        expected: PortType,
+    },
+    /// An output port was given a constant rather than a blackboard key.
+    UnmappedPort {
+        node: Identifier,
+        port: String,
+    },
+    /// A blackboard key is mapped to ports of different types.
+    ConflictingBlackboardKey {
+        key: Identifier,
+        expected: PortType,
+        found: PortType,
+    },
 }
 
+/// Trees hold their context through an `Arc`, so it cannot be changed while
+/// any tree compiled or loaded against it is alive. Hot reloading an
+/// executor, decorator or condition therefore means building a new context, or
+/// changing this one once no tree holds it, then compiling or loading the
+/// trees again.
+///
+/// As the context cannot change under them, and trees are only built by
+/// `compile` or by `from_bytes`, which verifies them, ticking a tree never
+/// meets a stale handle.
 #[derive(Debug)]
-pub struct BehaviourTree<CallType: Tuple> {
-    code: Vec<VecType>,
-    context: Rc<BehaviourContext<CallType>>,
-    node_count: usize,
+pub struct BehaviourTree<CallType> {
+    pub(crate) code: Vec<VecType>,
+    /// The pool of parameter sets used by the tree's nodes.
+    pub(crate) constants: Vec<Parameters>,
+    /// The index into `constants` of each node's parameters.
+    pub(crate) constant_indices: Vec<u32>,
+    /// The generation of the registry handle in each node's operand, or 0 for
+    /// nodes without one.
+    pub(crate) handle_generations: Vec<u32>,
+    pub(crate) context: Arc<BehaviourContext<CallType>>,
+    pub(crate) node_count: usize,
 }
 
-impl<Calltype: Tuple> BehaviourTree<Calltype> {
+impl<Calltype> BehaviourTree<Calltype> {
     pub fn code(&self) -> &Vec<VecType> {
         &self.code
     }
-    
+
+    pub fn constants(&self) -> &[Parameters] {
+        &self.constants
+    }
+
+    /// The parameters of the node at `position` in `code`.
+    pub fn parameters(&self, position: usize) -> &Parameters {
+        &self.constants[self.constant_indices[position / NODE_SIZE] as usize]
+    }
+
+    /// The registry handle of the executor, decorator or condition node at
+    /// `position` in `code`.
+    pub(crate) fn handle(&self, position: usize) -> RegistryHandle {
+        RegistryHandle::with_generation(
+            (self.code[position] & !ID_MASK) as usize,
+            self.handle_generations[position / NODE_SIZE],
+        )
+    }
+
     pub fn context(&self) -> &BehaviourContext<Calltype> {
         self.context.as_ref()
     }
-    
+
     pub fn node_count(&self) -> usize {
         self.node_count
     }
+
+    /// Ticks the tree once for the agent owning `state`, resuming any branches
+    /// it left running.
+    pub fn tick(&self, state: &mut TreeState, args: Calltype) -> TreeResult
+    where
+        Calltype: Clone,
+    {
+        self.tick_with_clock(state, &SystemClock, args)
+    }
+
+    /// Like `tick`, with time based nodes measuring time using `clock`.
+    pub fn tick_with_clock(
+        &self,
+        state: &mut TreeState,
+        clock: &dyn Clock,
+        args: Calltype,
+    ) -> TreeResult
+    where
+        Calltype: Clone,
+    {
+        self.tick_at(state, clock.now(), &args)
+    }
+
+    /// Ticks every agent in `states` once. All agents see the same time and
+    /// `args`, so per-agent inputs belong on their blackboards.
+    pub fn tick_batch(
+        &self,
+        states: &mut [TreeState],
+        clock: &dyn Clock,
+        args: Calltype,
+    ) -> Vec<TreeResult>
+    where
+        Calltype: Clone,
+    {
+        let now = clock.now();
+        states
+            .iter_mut()
+            .map(|state| self.tick_at(state, now, &args))
+            .collect()
+    }
+
+    /// Like `tick_batch`, spreading the agents over rayon's thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_tick_batch(
+        &self,
+        states: &mut [TreeState],
+        clock: &dyn Clock,
+        args: Calltype,
+    ) -> Vec<TreeResult>
+    where
+        Calltype: Clone + Sync,
+    {
+        use rayon::prelude::*;
+
+        let now = clock.now();
+        states
+            .par_iter_mut()
+            .map(|state| self.tick_at(state, now, &args))
+            .collect()
+    }
+
+    fn tick_at(&self, state: &mut TreeState, now: Duration, args: &Calltype) -> TreeResult
+    where
+        Calltype: Clone,
+    {
+        assert_eq!(
+            state.node_count(),
+            self.node_count,
+            "TreeState was created for a different tree"
+        );
+        state.begin_tick(now);
+        let result = self.tick_node(0, state, args);
+        for abandoned in state.end_tick() {
+            self.halt_node(abandoned, state, args);
+        }
+        result
+    }
+
+    /// Abandons every branch the agent owning `state` left running, running the
+    /// halt callbacks of its running executors. The next tick starts afresh.
+    pub fn halt(&self, state: &mut TreeState, args: Calltype)
+    where
+        Calltype: Clone,
+    {
+        for abandoned in state.take_executions() {
+            self.halt_node(abandoned, state, &args);
+        }
+    }
+
+    fn halt_node(&self, position: usize, state: &mut TreeState, args: &Calltype)
+    where
+        Calltype: Clone,
+    {
+        let word = self.code[position];
+        if (word >> OPCODE_SHIFT) as u8 == EXECUTOR_ID {
+            self.context
+                .call_halt(
+                    &self.handle(position),
+                    self.parameters(position),
+                    state.blackboard_mut(),
+                    args.clone(),
+                )
+                .expect(VALID_HANDLES);
+        }
+    }
+
+    fn tick_node(&self, position: usize, state: &mut TreeState, args: &Calltype) -> TreeResult
+    where
+        Calltype: Clone,
+    {
+        let word = self.code[position];
+        let operand = word & !ID_MASK;
+        let child_offset = self.code[position + 1] as usize;
+        let child = |idx: usize| child_offset + idx * NODE_SIZE;
+        let index_of = |child_position: usize| (child_position - child_offset) / NODE_SIZE;
+
+        let opcode = (word >> OPCODE_SHIFT) as u8;
+        let result = match opcode {
+            SEQUENCE_ID | REACTIVE_SEQUENCE_ID => {
+                // Reactive composites re-evaluate every child each tick, rather
+                // than resuming at the one left running.
+                let start = match opcode {
+                    REACTIVE_SEQUENCE_ID => 0,
+                    _ => state
+                        .resumed_children(position)
+                        .first()
+                        .map_or(0, |&c| index_of(c)),
+                };
+                state.enter(position);
+                let mut result = TreeResult::Success;
+                for idx in start..operand as usize {
+                    result = self.tick_node(child(idx), state, args);
+                    if result != TreeResult::Success {
+                        break;
+                    }
+                }
+                state.leave();
+                result
+            }
+            FALLBACK_ID | REACTIVE_FALLBACK_ID => {
+                let start = match opcode {
+                    REACTIVE_FALLBACK_ID => 0,
+                    _ => state
+                        .resumed_children(position)
+                        .first()
+                        .map_or(0, |&c| index_of(c)),
+                };
+                state.enter(position);
+                let mut result = TreeResult::Failure;
+                for idx in start..operand as usize {
+                    result = self.tick_node(child(idx), state, args);
+                    if result != TreeResult::Failure {
+                        break;
+                    }
+                }
+                state.leave();
+                result
+            }
+            PARALLEL_ID => {
+                let node = position / NODE_SIZE;
+                let child_count = (operand & PARALLEL_FIELD_MASK) as usize;
+                let failure = ((operand >> PARALLEL_FIELD_BITS) & PARALLEL_FIELD_MASK) as usize;
+                let success = (operand >> (PARALLEL_FIELD_BITS * 2)) as usize;
+
+                let mut children = state.resumed_children(position);
+                if children.is_empty() {
+                    children = (0..child_count).map(child).collect();
+                    state.node_mut(node).statuses = vec![TreeResult::Running; child_count];
+                }
+                state.enter(position);
+                let mut result = TreeResult::Running;
+                for child_position in children {
+                    let child_result = self.tick_node(child_position, state, args);
+                    let statuses = &mut state.node_mut(node).statuses;
+                    statuses[index_of(child_position)] = child_result;
+
+                    let count = |result| statuses.iter().filter(|&&s| s == result).count();
+                    let succeeded = count(TreeResult::Success);
+                    let failed = count(TreeResult::Failure);
+                    if succeeded >= success {
+                        result = TreeResult::Success;
+                    } else if failed >= failure || child_count - failed < success {
+                        result = TreeResult::Failure;
+                    }
+                    if result != TreeResult::Running {
+                        break;
+                    }
+                }
+                state.leave();
+                result
+            }
+            DECORATOR_ID => {
+                state.enter(position);
+                let result = self.tick_node(child(0), state, args);
+                state.leave();
+                self.context
+                    .call_decorator(
+                        &self.handle(position),
+                        self.parameters(position),
+                        args.clone(),
+                        result,
+                    )
+                    .expect(VALID_HANDLES)
+            }
+            EXECUTOR_ID => {
+                state.resume(position);
+                let result = self
+                    .context
+                    .call_executor(
+                        &self.handle(position),
+                        self.parameters(position),
+                        state.blackboard_mut(),
+                        args.clone(),
+                    )
+                    .expect(VALID_HANDLES);
+                if result == TreeResult::Running {
+                    state.suspend(position);
+                }
+                result
+            }
+            CONDITION_ID => {
+                let handle = self.handle(position);
+                if self
+                    .context
+                    .call_condition(&handle, state.blackboard(), args.clone())
+                    .expect(VALID_HANDLES)
+                {
+                    TreeResult::Success
+                } else {
+                    TreeResult::Failure
+                }
+            }
+            INVERTER_ID | FORCE_SUCCESS_ID | FORCE_FAILURE_ID => {
+                state.enter(position);
+                let result = self.tick_node(child(0), state, args);
+                state.leave();
+                match (opcode, result) {
+                    (_, TreeResult::Running) => TreeResult::Running,
+                    (INVERTER_ID, TreeResult::Success) => TreeResult::Failure,
+                    (INVERTER_ID, TreeResult::Failure) => TreeResult::Success,
+                    (FORCE_SUCCESS_ID, _) => TreeResult::Success,
+                    _ => TreeResult::Failure,
+                }
+            }
+            REPEAT_ID | RETRY_UNTIL_SUCCESS_ID => {
+                let node = position / NODE_SIZE;
+                if !state.is_resumed(position) {
+                    state.node_mut(node).counter = 0;
+                }
+                let again = if opcode == REPEAT_ID {
+                    TreeResult::Success
+                } else {
+                    TreeResult::Failure
+                };
+                state.enter(position);
+                let result = loop {
+                    let result = self.tick_node(child(0), state, args);
+                    if result != again {
+                        break result;
+                    }
+                    let counter = &mut state.node_mut(node).counter;
+                    *counter += 1;
+                    if *counter >= operand as usize {
+                        break result;
+                    }
+                };
+                state.leave();
+                result
+            }
+            UNTIL_FAILURE_ID => {
+                state.resume(position);
+                state.enter(position);
+                let result = self.tick_node(child(0), state, args);
+                state.leave();
+                // A successful child is restarted on the next tick, which
+                // resumes here rather than at the root.
+                if result == TreeResult::Success {
+                    state.suspend(position);
+                    TreeResult::Running
+                } else {
+                    result
+                }
+            }
+            TIMEOUT_ID => {
+                let node = position / NODE_SIZE;
+                let now = state.now();
+                if !state.is_resumed(position) {
+                    state.node_mut(node).timestamp = now;
+                }
+                if now.saturating_sub(state.node_mut(node).timestamp) >= millis(operand) {
+                    TreeResult::Failure
+                } else {
+                    state.enter(position);
+                    let result = self.tick_node(child(0), state, args);
+                    state.leave();
+                    result
+                }
+            }
+            COOLDOWN_ID => {
+                let node = position / NODE_SIZE;
+                let now = state.now();
+                if !state.is_resumed(position) && now < state.node_mut(node).timestamp {
+                    TreeResult::Failure
+                } else {
+                    state.enter(position);
+                    let result = self.tick_node(child(0), state, args);
+                    state.leave();
+                    if result != TreeResult::Running {
+                        state.node_mut(node).timestamp = now + millis(operand);
+                    }
+                    result
+                }
+            }
+            DELAY_ID => {
+                let node = position / NODE_SIZE;
+                let now = state.now();
+                if !state.is_resumed(position) {
+                    state.node_mut(node).timestamp = now;
+                }
+                state.resume(position);
+                if now.saturating_sub(state.node_mut(node).timestamp) < millis(operand) {
+                    state.suspend(position);
+                    TreeResult::Running
+                } else {
+                    state.enter(position);
+                    let result = self.tick_node(child(0), state, args);
+                    state.leave();
+                    result
+                }
+            }
+            opcode => unreachable!("unknown opcode {} at {}", opcode, position),
+        };
+
+        if result != TreeResult::Running {
+            for abandoned in state.abandon(position) {
+                self.halt_node(abandoned, state, args);
+            }
+        }
+        result
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     mod behaviour_node {
-        use std::rc::Rc;
+        use std::{sync::Arc, time::Duration};
 
-        use crate::{context::BehaviourContext, tree::{TreeCompilationError, EXECUTOR_ID, VecType, DECORATOR_ID, PARALLEL_ID, FALLBACK_ID, SEQUENCE_ID}};
+        use crate::{
+            context::BehaviourContext,
+            parameter::Parameters,
+            tree::{
+                TreeCompilationError, VecType, CONDITION_ID, DECORATOR_ID, EXECUTOR_ID,
+                FALLBACK_ID, INVERTER_ID, PARALLEL_ID, REPEAT_ID, SEQUENCE_ID,
+            },
+            verifier::MAX_DEPTH,
+        };
 
-        use super::BehaviourNode as Subject;
+        use super::{BehaviourNode as Subject, ParallelPolicy};
 
         pub mod test_funcs {
-            use crate::state::TreeResult;
+            use crate::{blackboard::Blackboard, parameter::Parameters, state::TreeResult};
 
-            pub fn executor(_: ()) -> TreeResult {
+            pub fn executor(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
                 TreeResult::Success
             }
 
-            pub fn decorator(_: TreeResult, _1: ()) -> TreeResult {
+            pub fn decorator(_: TreeResult, _: &Parameters, _: ()) -> TreeResult {
                 TreeResult::Success
             }
+
+            pub fn condition(_: &Blackboard, _: ()) -> bool {
+                true
+            }
         }
 
         #[test]
         fn compile_fails_first_node_control_sequence() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Sequence {
                 children: Vec::new(),
             };
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::InitialNonRootNode));
         }
 
         #[test]
         fn compile_fails_first_node_control_fallback() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Fallback {
                 children: Vec::new(),
             };
-            assert!(subject.compile::<()>(Rc::downgrade(&ctx)).is_err());
+            assert!(subject.compile::<()>(Arc::downgrade(&ctx)).is_err());
         }
 
         #[test]
         fn compile_fails_first_node_control_parallel() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Parallel {
+                policy: ParallelPolicy::RequireAll,
                 children: Vec::new(),
             };
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::InitialNonRootNode));
         }
 
         #[test]
         fn compile_fails_first_node_control_decorator() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Decorator {
                 name: "test".into(),
-                child: Box::new(Subject::Executor("".into())),
+                parameters: Parameters::new(),
+                child: Box::new(Subject::Executor("".into(), Parameters::new())),
             };
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::InitialNonRootNode));
         }
 
         #[test]
         fn compile_fails_first_node_execute() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
-            let subject = Subject::Executor("".into());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
+            let subject = Subject::Executor("".into(), Parameters::new());
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::InitialNonRootNode));
         }
 
         #[test]
         fn compile_fails_no_nodes_control_sequence() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Root(Box::new(Subject::Sequence {
                 children: Vec::new(),
             }));
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::NoNodes));
         }
 
         #[test]
         fn compile_fails_no_nodes_control_fallback() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Root(Box::new(Subject::Fallback {
                 children: Vec::new(),
             }));
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::NoNodes));
         }
 
         #[test]
         fn compile_fails_no_nodes_control_parallel() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Root(Box::new(Subject::Parallel {
+                policy: ParallelPolicy::RequireAll,
                 children: Vec::new(),
             }));
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
+                .is_err_and(|err| err == TreeCompilationError::NoNodes));
+        }
+
+        #[test]
+        fn compile_fails_no_nodes_nested_control() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+            let subject = Subject::Root(Box::new(Subject::Sequence {
+                children: vec![
+                    Subject::Fallback {
+                        children: Vec::new(),
+                    },
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
+            }));
+            assert!(subject
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::NoNodes));
         }
 
         #[test]
         fn compile_fails_unknown_decorator_control_decorator() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
             let subject = Subject::Root(Box::new(Subject::Decorator {
                 name: "decorator".into(),
-                child: Box::new(Subject::Executor("".into())),
+                parameters: Parameters::new(),
+                child: Box::new(Subject::Executor("".into(), Parameters::new())),
             }));
-            assert!(subject.compile::<()>(Rc::downgrade(&ctx)).is_err_and(
+            assert!(subject.compile::<()>(Arc::downgrade(&ctx)).is_err_and(
                 |err| err == TreeCompilationError::UnknownDecorator("decorator".into())
             ));
         }
 
         #[test]
         fn compile_fails_unknown_handler_executor() {
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(BehaviourContext::new());
-            let subject = Subject::Root(Box::new(Subject::Executor("executor".into())));
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
+            let subject = Subject::Root(Box::new(Subject::Executor(
+                "executor".into(),
+                Parameters::new(),
+            )));
             assert!(subject
-                .compile::<()>(Rc::downgrade(&ctx))
+                .compile::<()>(Arc::downgrade(&ctx))
                 .is_err_and(|err| err == TreeCompilationError::UnknownExecutor("executor".into())));
         }
 
@@ -335,10 +1097,13 @@ mod tests {
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
-            let subject = Subject::Root(Box::new(Subject::Executor("exec".into())));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let subject = Subject::Root(Box::new(Subject::Executor(
+                "exec".into(),
+                Parameters::new(),
+            )));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
 
             if let Ok(tree) = res {
@@ -347,6 +1112,198 @@ mod tests {
             }
         }
 
+        #[test]
+        fn compile_success_parameters() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            context
+                .register_decorator(&"decorate".into(), test_funcs::decorator)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let speed = Parameters::new().with("speed", 2);
+            let subject = Subject::Root(Box::new(Subject::Sequence {
+                children: vec![
+                    Subject::Decorator {
+                        name: "decorate".into(),
+                        parameters: Parameters::new().with("label", "guard"),
+                        child: Box::new(Subject::Executor("exec".into(), speed.clone())),
+                    },
+                    Subject::Executor("exec".into(), speed.clone()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
+            }));
+            let tree = subject.compile(Arc::downgrade(&ctx)).unwrap();
+
+            // Identical parameter sets share a single entry in the pool.
+            assert_eq!(tree.constants().len(), 3);
+            assert!(tree.parameters(0).is_empty());
+            assert_eq!(tree.parameters(2).text("label"), Some("guard"));
+            assert_eq!(tree.parameters(4), &speed);
+            assert!(tree.parameters(6).is_empty());
+            assert_eq!(tree.parameters(8), &speed);
+        }
+
+        mod ports {
+            use std::sync::Arc;
+
+            use crate::{
+                context::{BehaviourContext, ExecutorOptions},
+                parameter::{Parameter, Parameters, Port, PortType},
+                tree::{BehaviourNode as Subject, BehaviourTree, TreeCompilationError},
+            };
+
+            use super::test_funcs;
+
+            fn context() -> Arc<BehaviourContext<()>> {
+                let mut context = BehaviourContext::new();
+                context
+                    .register_executor_with_options(
+                        &"move".into(),
+                        test_funcs::executor,
+                        ExecutorOptions::new().ports([
+                            Port::input("speed", PortType::Float),
+                            Port::output("position", PortType::Integer),
+                        ]),
+                    )
+                    .unwrap();
+                context
+                    .register_executor_with_options(
+                        &"say".into(),
+                        test_funcs::executor,
+                        ExecutorOptions::new().ports([Port::input("line", PortType::Text)]),
+                    )
+                    .unwrap();
+                context
+                    .register_executor(&"exec".into(), test_funcs::executor)
+                    .unwrap();
+                Arc::new(context)
+            }
+
+            fn compile(children: Vec<Subject>) -> Result<BehaviourTree<()>, TreeCompilationError> {
+                Subject::Root(Box::new(Subject::Sequence { children }))
+                    .compile(Arc::downgrade(&context()))
+            }
+
+            fn key(name: &str) -> Parameter {
+                Parameter::BlackboardKey(name.into())
+            }
+
+            fn move_to(speed: impl Into<Parameter>, position: Parameter) -> Subject {
+                let parameters = Parameters::new()
+                    .with("speed", speed)
+                    .with("position", position);
+                Subject::Executor("move".into(), parameters)
+            }
+
+            #[test]
+            fn success() {
+                let say =
+                    Subject::Executor("say".into(), Parameters::new().with("line", key("line")));
+                let exec = Subject::Executor("exec".into(), Parameters::new().with("any", 1));
+                assert!(compile(vec![
+                    move_to(1.5, key("x")),
+                    move_to(key("speed"), key("x")),
+                    say,
+                    exec
+                ])
+                .is_ok());
+            }
+
+            #[test]
+            fn missing_port() {
+                let subject =
+                    Subject::Executor("move".into(), Parameters::new().with("speed", 1.5));
+                assert_eq!(
+                    compile(vec![subject]).unwrap_err(),
+                    TreeCompilationError::MissingPort {
+                        node: "move".into(),
+                        port: "position".into()
+                    }
+                );
+            }
+
+            #[test]
+            fn unknown_port() {
+                let subject = Subject::Executor(
+                    "say".into(),
+                    Parameters::new().with("line", "hi").with("volume", 3),
+                );
+                assert_eq!(
+                    compile(vec![subject]).unwrap_err(),
+                    TreeCompilationError::UnknownPort {
+                        node: "say".into(),
+                        port: "volume".into()
+                    }
+                );
+            }
+
+            #[test]
+            fn mistyped_port() {
+                assert_eq!(
+                    compile(vec![move_to(1, key("x"))]).unwrap_err(),
+                    TreeCompilationError::MistypedPort {
+                        node: "move".into(),
+                        port: "speed".into(),
+                        expected: PortType::Float
+                    }
+                );
+            }
+
+            #[test]
+            fn unmapped_port() {
+                assert_eq!(
+                    compile(vec![move_to(1.5, Parameter::Integer(3))]).unwrap_err(),
+                    TreeCompilationError::UnmappedPort {
+                        node: "move".into(),
+                        port: "position".into()
+                    }
+                );
+            }
+
+            #[test]
+            fn conflicting_blackboard_key() {
+                let say = Subject::Executor("say".into(), Parameters::new().with("line", key("x")));
+                assert_eq!(
+                    compile(vec![move_to(1.5, key("x")), say]).unwrap_err(),
+                    TreeCompilationError::ConflictingBlackboardKey {
+                        key: "x".into(),
+                        expected: PortType::Integer,
+                        found: PortType::Text
+                    }
+                );
+            }
+        }
+
+        #[test]
+        fn compile_fails_unknown_condition() {
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(BehaviourContext::new());
+            let subject = Subject::Root(Box::new(Subject::Condition("condition".into())));
+            assert!(subject.compile::<()>(Arc::downgrade(&ctx)).is_err_and(
+                |err| err == TreeCompilationError::UnknownCondition("condition".into())
+            ));
+        }
+
+        #[test]
+        fn compile_success_condition() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_condition(&"check".into(), test_funcs::condition)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let subject = Subject::Root(Box::new(Subject::Condition("check".into())));
+            let res = subject.compile(Arc::downgrade(&ctx));
+            assert!(res.is_ok());
+
+            if let Ok(tree) = res {
+                assert_eq!(tree.node_count, 1);
+                assert_eq!(tree.code, vec![(CONDITION_ID as VecType) << 24, 0]);
+            }
+        }
+
         #[test]
         fn compile_success_decorator() {
             let mut context = BehaviourContext::new();
@@ -356,101 +1313,330 @@ mod tests {
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Decorator {
                 name: "decorate".into(),
-                child: Box::new(Subject::Executor("exec".into())),
+                parameters: Parameters::new(),
+                child: Box::new(Subject::Executor("exec".into(), Parameters::new())),
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 2);
-                assert_eq!(tree.code, vec![(DECORATOR_ID as VecType) << 24, 2, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        (DECORATOR_ID as VecType) << 24,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
-        
+
         #[test]
         fn compile_success_parallel() {
             let mut context = BehaviourContext::new();
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Parallel {
-                children: vec![Subject::Executor("exec".into())],
+                policy: ParallelPolicy::RequireAll,
+                children: vec![Subject::Executor("exec".into(), Parameters::new())],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 2);
-                assert_eq!(tree.code, vec![((PARALLEL_ID as VecType) << 24) | 1, 2, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        ((PARALLEL_ID as VecType) << 24) | (1 << 16) | (1 << 8) | 1,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
-        
+
         #[test]
         fn compile_success_parallel_multiple_children() {
             let mut context = BehaviourContext::new();
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Parallel {
-                children: vec![Subject::Executor("exec".into()), Subject::Executor("exec".into())],
+                policy: ParallelPolicy::RequireAll,
+                children: vec![
+                    Subject::Executor("exec".into(), Parameters::new()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 3);
-                assert_eq!(tree.code, vec![((PARALLEL_ID as VecType) << 24) | 2, 2, (EXECUTOR_ID as VecType) << 24, 0, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        ((PARALLEL_ID as VecType) << 24) | (2 << 16) | (1 << 8) | 2,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
-        
+
         #[test]
-        fn compile_success_fallback() {
+        fn compile_success_parallel_threshold() {
             let mut context = BehaviourContext::new();
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
-            let subject = Subject::Root(Box::new(Subject::Fallback {
-                children: vec![Subject::Executor("exec".into())],
+            let subject = Subject::Root(Box::new(Subject::Parallel {
+                policy: ParallelPolicy::Threshold {
+                    success: 2,
+                    failure: 3,
+                },
+                children: vec![
+                    Subject::Executor("exec".into(), Parameters::new()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
-            assert!(res.is_ok());
-            
-            if let Ok(tree) = res {
+            let tree = subject.compile(Arc::downgrade(&ctx)).unwrap();
+
+            assert_eq!(
+                tree.code[0],
+                ((PARALLEL_ID as VecType) << 24) | (2 << 16) | (3 << 8) | 3
+            );
+        }
+
+        #[test]
+        fn compile_fails_invalid_parallel_policy() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            for policy in [
+                ParallelPolicy::Threshold {
+                    success: 0,
+                    failure: 1,
+                },
+                ParallelPolicy::Threshold {
+                    success: 1,
+                    failure: 3,
+                },
+            ] {
+                let subject = Subject::Root(Box::new(Subject::Parallel {
+                    policy,
+                    children: vec![
+                        Subject::Executor("exec".into(), Parameters::new()),
+                        Subject::Executor("exec".into(), Parameters::new()),
+                    ],
+                }));
+                assert!(subject
+                    .compile(Arc::downgrade(&ctx))
+                    .is_err_and(|err| err == TreeCompilationError::InvalidParallelPolicy(policy)));
+            }
+        }
+
+        #[test]
+        fn compile_fails_too_many_children_parallel() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let subject = Subject::Root(Box::new(Subject::Parallel {
+                policy: ParallelPolicy::RequireAll,
+                children: (0..256)
+                    .map(|_| Subject::Executor("exec".into(), Parameters::new()))
+                    .collect(),
+            }));
+            assert!(subject
+                .compile(Arc::downgrade(&ctx))
+                .is_err_and(|err| err == TreeCompilationError::TooManyChildNodes));
+        }
+
+        #[test]
+        fn compile_fail_too_deep() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+            let chain = |levels| {
+                let leaf = Subject::Executor("exec".into(), Parameters::new());
+                Subject::Root(Box::new((1..levels).fold(leaf, |child, _| {
+                    Subject::Inverter {
+                        child: Box::new(child),
+                    }
+                })))
+            };
+
+            assert!(chain(MAX_DEPTH).compile(Arc::downgrade(&ctx)).is_ok());
+            assert_eq!(
+                chain(MAX_DEPTH + 1).compile(Arc::downgrade(&ctx)).err(),
+                Some(TreeCompilationError::TooDeep { limit: MAX_DEPTH })
+            );
+        }
+
+        #[test]
+        fn compile_success_builtin_decorators() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let subject = Subject::Root(Box::new(Subject::Inverter {
+                child: Box::new(Subject::Repeat {
+                    count: 3,
+                    child: Box::new(Subject::Executor("exec".into(), Parameters::new())),
+                }),
+            }));
+            let res = subject.compile(Arc::downgrade(&ctx));
+            assert!(res.is_ok());
+
+            if let Ok(tree) = res {
+                assert_eq!(tree.node_count, 3);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        (INVERTER_ID as VecType) << 24,
+                        2,
+                        ((REPEAT_ID as VecType) << 24) | 3,
+                        4,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
+            }
+        }
+
+        #[test]
+        fn compile_fails_invalid_count() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let subject = Subject::Root(Box::new(Subject::Repeat {
+                count: 0,
+                child: Box::new(Subject::Executor("exec".into(), Parameters::new())),
+            }));
+            assert!(subject
+                .compile(Arc::downgrade(&ctx))
+                .is_err_and(|err| err == TreeCompilationError::InvalidCount(0)));
+
+            let subject = Subject::Root(Box::new(Subject::RetryUntilSuccess {
+                attempts: 1 << 24,
+                child: Box::new(Subject::Executor("exec".into(), Parameters::new())),
+            }));
+            assert!(subject
+                .compile(Arc::downgrade(&ctx))
+                .is_err_and(|err| err == TreeCompilationError::InvalidCount(1 << 24)));
+        }
+
+        #[test]
+        fn compile_fails_invalid_duration() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+            let duration = Duration::from_millis(1 << 24);
+
+            let subject = Subject::Root(Box::new(Subject::Timeout {
+                duration,
+                child: Box::new(Subject::Executor("exec".into(), Parameters::new())),
+            }));
+            assert!(subject
+                .compile(Arc::downgrade(&ctx))
+                .is_err_and(|err| err == TreeCompilationError::InvalidDuration(duration)));
+        }
+
+        #[test]
+        fn compile_success_fallback() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"exec".into(), test_funcs::executor)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            let subject = Subject::Root(Box::new(Subject::Fallback {
+                children: vec![Subject::Executor("exec".into(), Parameters::new())],
+            }));
+            let res = subject.compile(Arc::downgrade(&ctx));
+            assert!(res.is_ok());
+
+            if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 2);
-                assert_eq!(tree.code, vec![((FALLBACK_ID as VecType) << 24) | 1, 2, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        ((FALLBACK_ID as VecType) << 24) | 1,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
-        
+
         #[test]
         fn compile_success_fallback_multiple_children() {
             let mut context = BehaviourContext::new();
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Fallback {
-                children: vec![Subject::Executor("exec".into()), Subject::Executor("exec".into())],
+                children: vec![
+                    Subject::Executor("exec".into(), Parameters::new()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 3);
-                assert_eq!(tree.code, vec![((FALLBACK_ID as VecType) << 24) | 2, 2, (EXECUTOR_ID as VecType) << 24, 0, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        ((FALLBACK_ID as VecType) << 24) | 2,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
-        
+
         #[test]
         fn compile_success_sequence() {
             let mut context = BehaviourContext::new();
@@ -460,19 +1646,19 @@ mod tests {
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Sequence {
-                children: vec![Subject::Executor("exec".into())],
+                children: vec![Subject::Executor("exec".into(), Parameters::new())],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 2);
             }
         }
-        
+
         #[test]
         fn compile_success_sequence_multiple_children() {
             let mut context = BehaviourContext::new();
@@ -482,20 +1668,1220 @@ mod tests {
             context
                 .register_executor(&"exec".into(), test_funcs::executor)
                 .unwrap();
-            let ctx: Rc<BehaviourContext<()>> = Rc::new(context);
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
 
             let subject = Subject::Root(Box::new(Subject::Sequence {
-                children: vec![Subject::Executor("exec".into()), Subject::Executor("exec".into())],
+                children: vec![
+                    Subject::Executor("exec".into(), Parameters::new()),
+                    Subject::Executor("exec".into(), Parameters::new()),
+                ],
             }));
-            let res = subject.compile(Rc::downgrade(&ctx));
+            let res = subject.compile(Arc::downgrade(&ctx));
             assert!(res.is_ok());
-            
+
             if let Ok(tree) = res {
                 assert_eq!(tree.node_count, 3);
-                assert_eq!(tree.code, vec![((SEQUENCE_ID as VecType) << 24) | 2, 2, (EXECUTOR_ID as VecType) << 24, 0, (EXECUTOR_ID as VecType) << 24, 0]);
+                assert_eq!(
+                    tree.code,
+                    vec![
+                        ((SEQUENCE_ID as VecType) << 24) | 2,
+                        2,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0,
+                        (EXECUTOR_ID as VecType) << 24,
+                        0
+                    ]
+                );
             }
         }
     }
 
-    mod behaviour_tree {}
+    mod behaviour_tree {
+        use std::{sync::Arc, time::Duration};
+
+        use crate::{
+            blackboard::Blackboard,
+            clock::ManualClock,
+            context::BehaviourContext,
+            parameter::{Parameter, Parameters},
+            state::{TreeResult, TreeState},
+        };
+
+        use super::{BehaviourNode as Node, BehaviourTree, ParallelPolicy};
+
+        pub mod test_funcs {
+            use crate::{blackboard::Blackboard, parameter::Parameters, state::TreeResult};
+
+            pub fn success(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+                TreeResult::Success
+            }
+
+            pub fn failure(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+                TreeResult::Failure
+            }
+
+            pub fn running(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+                TreeResult::Running
+            }
+
+            pub fn invert(result: TreeResult, _: &Parameters, _: ()) -> TreeResult {
+                match result {
+                    TreeResult::Success => TreeResult::Failure,
+                    TreeResult::Failure => TreeResult::Success,
+                    TreeResult::Running => TreeResult::Running,
+                }
+            }
+        }
+
+        fn context() -> Arc<BehaviourContext<()>> {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"success".into(), test_funcs::success)
+                .unwrap();
+            context
+                .register_executor(&"failure".into(), test_funcs::failure)
+                .unwrap();
+            context
+                .register_executor(&"running".into(), test_funcs::running)
+                .unwrap();
+            context
+                .register_decorator(&"invert".into(), test_funcs::invert)
+                .unwrap();
+            context
+                .register_condition(&"true".into(), |_: &Blackboard, _| true)
+                .unwrap();
+            context
+                .register_condition(&"false".into(), |_: &Blackboard, _| false)
+                .unwrap();
+            Arc::new(context)
+        }
+
+        fn tick(ctx: &Arc<BehaviourContext<()>>, node: Node) -> TreeResult {
+            let tree = Node::Root(Box::new(node))
+                .compile(Arc::downgrade(ctx))
+                .unwrap();
+            tree.tick(&mut TreeState::new(&tree), ())
+        }
+
+        fn exec(id: &str) -> Node {
+            Node::Executor(id.into(), Parameters::new())
+        }
+
+        #[test]
+        fn tick_executor() {
+            let ctx = context();
+            assert_eq!(tick(&ctx, exec("success")), TreeResult::Success);
+            assert_eq!(tick(&ctx, exec("failure")), TreeResult::Failure);
+            assert_eq!(tick(&ctx, exec("running")), TreeResult::Running);
+        }
+
+        #[test]
+        fn tick_condition() {
+            let ctx = context();
+            assert_eq!(
+                tick(&ctx, Node::Condition("true".into())),
+                TreeResult::Success
+            );
+            let subject = Node::Sequence {
+                children: vec![Node::Condition("false".into()), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+        }
+
+        #[test]
+        fn tick_decorator() {
+            let ctx = context();
+            let subject = Node::Decorator {
+                name: "invert".into(),
+                parameters: Parameters::new(),
+                child: Box::new(exec("failure")),
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+        }
+
+        #[test]
+        fn tick_parameters() {
+            fn step(parameters: &Parameters, blackboard: &mut Blackboard, _: ()) -> TreeResult {
+                let key = parameters.blackboard_key("position").unwrap();
+                let position = blackboard.get::<i64>(key).map_or(0, |p| *p);
+                let speed = parameters.integer("speed").unwrap();
+                blackboard.set(key, position + speed).unwrap();
+                TreeResult::Success
+            }
+
+            fn expect(result: TreeResult, parameters: &Parameters, _: ()) -> TreeResult {
+                match parameters.text("expect") {
+                    Some("failure") => TreeResult::Failure,
+                    _ => result,
+                }
+            }
+
+            let mut context = BehaviourContext::new();
+            context.register_executor(&"step".into(), step).unwrap();
+            context
+                .register_decorator(&"expect".into(), expect)
+                .unwrap();
+            let ctx = Arc::new(context);
+
+            let step_by = |speed: i64| {
+                let parameters = Parameters::new()
+                    .with("speed", speed)
+                    .with("position", Parameter::BlackboardKey("x".into()));
+                Node::Executor("step".into(), parameters)
+            };
+            let tree = Node::Root(Box::new(Node::Decorator {
+                name: "expect".into(),
+                parameters: Parameters::new().with("expect", "failure"),
+                child: Box::new(Node::Sequence {
+                    children: vec![step_by(1), step_by(3)],
+                }),
+            }))
+            .compile(Arc::downgrade(&ctx))
+            .unwrap();
+            let mut state = TreeState::new(&tree);
+
+            assert_eq!(tree.tick(&mut state, ()), TreeResult::Failure);
+            assert_eq!(state.blackboard().get::<i64>(&"x".into()), Ok(&4));
+        }
+
+        #[test]
+        fn tick_sequence() {
+            let ctx = context();
+            let subject = Node::Sequence {
+                children: vec![exec("success"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+
+            let subject = Node::Sequence {
+                children: vec![exec("success"), exec("failure"), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+
+            let subject = Node::Sequence {
+                children: vec![exec("running"), exec("failure")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Running);
+        }
+
+        #[test]
+        fn tick_fallback() {
+            let ctx = context();
+            let subject = Node::Fallback {
+                children: vec![exec("failure"), exec("failure")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+
+            let subject = Node::Fallback {
+                children: vec![exec("failure"), exec("success"), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+
+            let subject = Node::Fallback {
+                children: vec![exec("running"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Running);
+        }
+
+        #[test]
+        fn tick_parallel() {
+            let ctx = context();
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireAll,
+                children: vec![exec("success"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireAll,
+                children: vec![exec("running"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Running);
+
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireAll,
+                children: vec![exec("running"), exec("failure")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+        }
+
+        #[test]
+        fn tick_parallel_require_one() {
+            let ctx = context();
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireOne,
+                children: vec![exec("failure"), exec("running"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireOne,
+                children: vec![exec("failure"), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Running);
+
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::RequireOne,
+                children: vec![exec("failure"), exec("failure")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+        }
+
+        #[test]
+        fn tick_parallel_threshold() {
+            let ctx = context();
+            let policy = ParallelPolicy::Threshold {
+                success: 2,
+                failure: 2,
+            };
+            let subject = Node::Parallel {
+                policy,
+                children: vec![exec("success"), exec("running"), exec("success")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+
+            let subject = Node::Parallel {
+                policy,
+                children: vec![exec("success"), exec("failure"), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Running);
+
+            let subject = Node::Parallel {
+                policy,
+                children: vec![exec("failure"), exec("running"), exec("failure")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+
+            // Success is unreachable once two of three children have failed,
+            // even though the failure threshold is not met.
+            let subject = Node::Parallel {
+                policy: ParallelPolicy::Threshold {
+                    success: 2,
+                    failure: 3,
+                },
+                children: vec![exec("failure"), exec("failure"), exec("running")],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Failure);
+        }
+
+        #[test]
+        fn tick_builtin_decorators() {
+            let ctx = context();
+            let cases = [
+                (
+                    "success",
+                    TreeResult::Failure,
+                    TreeResult::Success,
+                    TreeResult::Failure,
+                ),
+                (
+                    "failure",
+                    TreeResult::Success,
+                    TreeResult::Success,
+                    TreeResult::Failure,
+                ),
+                (
+                    "running",
+                    TreeResult::Running,
+                    TreeResult::Running,
+                    TreeResult::Running,
+                ),
+            ];
+            for (id, inverted, forced_success, forced_failure) in cases {
+                let subject = Node::Inverter {
+                    child: Box::new(exec(id)),
+                };
+                assert_eq!(tick(&ctx, subject), inverted);
+                let subject = Node::ForceSuccess {
+                    child: Box::new(exec(id)),
+                };
+                assert_eq!(tick(&ctx, subject), forced_success);
+                let subject = Node::ForceFailure {
+                    child: Box::new(exec(id)),
+                };
+                assert_eq!(tick(&ctx, subject), forced_failure);
+            }
+        }
+
+        #[test]
+        fn tick_nested() {
+            let ctx = context();
+            let subject = Node::Sequence {
+                children: vec![
+                    Node::Fallback {
+                        children: vec![exec("failure"), exec("success")],
+                    },
+                    Node::Decorator {
+                        name: "invert".into(),
+                        parameters: Parameters::new(),
+                        child: Box::new(Node::Sequence {
+                            children: vec![exec("success"), exec("failure")],
+                        }),
+                    },
+                    exec("success"),
+                ],
+            };
+            assert_eq!(tick(&ctx, subject), TreeResult::Success);
+        }
+
+        #[test]
+        fn tick_across_threads() {
+            fn assert_send_sync<T: Send + Sync>() {}
+            fn assert_send<T: Send>() {}
+            assert_send_sync::<BehaviourTree<()>>();
+            assert_send::<TreeState>();
+
+            let ctx = context();
+            let tree = Node::Root(Box::new(Node::Fallback {
+                children: vec![exec("failure"), exec("success")],
+            }))
+            .compile(Arc::downgrade(&ctx))
+            .unwrap();
+            let mut states: Vec<TreeState> = (0..4).map(|_| TreeState::new(&tree)).collect();
+
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = states
+                    .iter_mut()
+                    .map(|state| scope.spawn(|| tree.tick(state, ())))
+                    .collect();
+                for handle in handles {
+                    assert_eq!(handle.join().unwrap(), TreeResult::Success);
+                }
+            });
+        }
+
+        fn batch_tree(ctx: &Arc<BehaviourContext<()>>) -> BehaviourTree<()> {
+            Node::Root(Box::new(Node::Sequence {
+                children: vec![
+                    Node::Condition("true".into()),
+                    Node::Cooldown {
+                        duration: Duration::from_secs(1),
+                        child: Box::new(exec("success")),
+                    },
+                ],
+            }))
+            .compile(Arc::downgrade(ctx))
+            .unwrap()
+        }
+
+        #[test]
+        fn tick_batch() {
+            let ctx = context();
+            let tree = batch_tree(&ctx);
+            let mut states: Vec<TreeState> = (0..3).map(|_| TreeState::new(&tree)).collect();
+            let mut clock = ManualClock::default();
+
+            assert_eq!(
+                tree.tick_batch(&mut states[..2], &clock, ()),
+                vec![TreeResult::Success; 2]
+            );
+            assert_eq!(
+                tree.tick_batch(&mut states, &clock, ()),
+                vec![
+                    TreeResult::Failure,
+                    TreeResult::Failure,
+                    TreeResult::Success
+                ]
+            );
+            clock.advance(Duration::from_millis(999));
+            assert_eq!(
+                tree.tick_batch(&mut states, &clock, ()),
+                vec![TreeResult::Failure; 3]
+            );
+            clock.advance(Duration::from_millis(1));
+            assert_eq!(
+                tree.tick_batch(&mut states, &clock, ()),
+                vec![TreeResult::Success; 3]
+            );
+        }
+
+        #[cfg(feature = "rayon")]
+        #[test]
+        fn par_tick_batch() {
+            let ctx = context();
+            let tree = batch_tree(&ctx);
+            let mut states: Vec<TreeState> = (0..64).map(|_| TreeState::new(&tree)).collect();
+            let clock = ManualClock::default();
+            tree.tick_batch(&mut states[..32], &clock, ());
+
+            let results = tree.par_tick_batch(&mut states, &clock, ());
+            assert_eq!(results[..32], vec![TreeResult::Failure; 32]);
+            assert_eq!(results[32..], vec![TreeResult::Success; 32]);
+        }
+
+        #[test]
+        fn tick_reregistered_entries() {
+            let mut context = BehaviourContext::new();
+            context
+                .register_executor(&"removed".into(), test_funcs::failure)
+                .unwrap();
+            context
+                .register_decorator(&"invert".into(), test_funcs::invert)
+                .unwrap();
+            context
+                .register_condition(&"ready".into(), |_: &Blackboard, _| false)
+                .unwrap();
+            assert!(context.remove_executor(&"removed".into()));
+            context
+                .register_executor(&"success".into(), test_funcs::success)
+                .unwrap();
+            context
+                .replace_decorator(&"invert".into(), |result, _: &Parameters, _| result)
+                .unwrap();
+            context
+                .replace_condition(&"ready".into(), |_: &Blackboard, _| true)
+                .unwrap();
+            let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+            // Every handle is now of a later generation than the first.
+            let tree = Node::Root(Box::new(Node::Sequence {
+                children: vec![
+                    Node::Condition("ready".into()),
+                    Node::Decorator {
+                        name: "invert".into(),
+                        parameters: Parameters::new(),
+                        child: Box::new(exec("success")),
+                    },
+                ],
+            }))
+            .compile(Arc::downgrade(&ctx))
+            .unwrap();
+            assert_eq!(
+                tree.tick(&mut TreeState::new(&tree), ()),
+                TreeResult::Success
+            );
+
+            let loaded =
+                BehaviourTree::from_bytes(&tree.to_bytes().unwrap(), Arc::downgrade(&ctx)).unwrap();
+            assert_eq!(
+                loaded.tick(&mut TreeState::new(&loaded), ()),
+                TreeResult::Success
+            );
+        }
+
+        mod blackboard {
+            use std::sync::Arc;
+
+            use crate::{
+                blackboard::Blackboard,
+                context::BehaviourContext,
+                parameter::Parameters,
+                state::{TreeResult, TreeState},
+                tree::BehaviourNode as Node,
+            };
+
+            fn perceive(_: &Parameters, blackboard: &mut Blackboard, _: ()) -> TreeResult {
+                blackboard.set(&"target".into(), 7u32).unwrap();
+                TreeResult::Success
+            }
+
+            fn attack(_: &Parameters, blackboard: &mut Blackboard, _: ()) -> TreeResult {
+                match blackboard.get::<u32>(&"target".into()) {
+                    Ok(&target) => {
+                        blackboard.set(&"attacked".into(), target).unwrap();
+                        TreeResult::Success
+                    }
+                    Err(_) => TreeResult::Failure,
+                }
+            }
+
+            #[test]
+            fn executors_share_blackboard() {
+                let mut context = BehaviourContext::new();
+                context
+                    .register_executor(&"perceive".into(), perceive)
+                    .unwrap();
+                context.register_executor(&"attack".into(), attack).unwrap();
+                let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+
+                let attack_only =
+                    Node::Root(Box::new(Node::Executor("attack".into(), Parameters::new())))
+                        .compile(Arc::downgrade(&ctx))
+                        .unwrap();
+                let tree = Node::Root(Box::new(Node::Sequence {
+                    children: vec![
+                        Node::Executor("perceive".into(), Parameters::new()),
+                        Node::Executor("attack".into(), Parameters::new()),
+                    ],
+                }))
+                .compile(Arc::downgrade(&ctx))
+                .unwrap();
+
+                let mut state = TreeState::new(&attack_only);
+                assert_eq!(attack_only.tick(&mut state, ()), TreeResult::Failure);
+
+                let mut state = TreeState::new(&tree);
+                assert_eq!(tree.tick(&mut state, ()), TreeResult::Success);
+                assert_eq!(state.blackboard().get::<u32>(&"attacked".into()), Ok(&7));
+
+                let mut other = TreeState::new(&tree);
+                other
+                    .blackboard_mut()
+                    .set(&"attacked".into(), 0u32)
+                    .unwrap();
+                state.clear();
+                assert_eq!(other.blackboard().get::<u32>(&"attacked".into()), Ok(&0));
+                assert!(state.blackboard().contains(&"target".into()));
+            }
+        }
+
+        mod reactive {
+            use std::{sync::Arc, time::Duration};
+
+            use crate::{
+                blackboard::Blackboard,
+                clock::ManualClock,
+                context::{BehaviourContext, ExecutorOptions},
+                parameter::{Parameters, Port, PortType},
+                state::{TreeResult, TreeState},
+                tree::{
+                    BehaviourNode as Node, BehaviourTree, ParallelPolicy, TreeCompilationError,
+                },
+            };
+
+            fn visible(blackboard: &Blackboard, _: ()) -> bool {
+                blackboard.get::<bool>(&"visible".into()) == Ok(&true)
+            }
+
+            fn chase(_: &Parameters, blackboard: &mut Blackboard, _: ()) -> TreeResult {
+                let chased = blackboard.get::<u32>(&"chased".into()).map_or(0, |c| *c);
+                blackboard.set(&"chased".into(), chased + 1).unwrap();
+                TreeResult::Running
+            }
+
+            fn compile(ctx: &Arc<BehaviourContext<()>>, node: Node) -> BehaviourTree<()> {
+                Node::Root(Box::new(node))
+                    .compile(Arc::downgrade(ctx))
+                    .unwrap()
+            }
+
+            fn context() -> Arc<BehaviourContext<()>> {
+                let mut context = BehaviourContext::new();
+                context
+                    .register_condition(&"visible".into(), visible)
+                    .unwrap();
+                context
+                    .register_executor_with_options(
+                        &"chase".into(),
+                        chase,
+                        ExecutorOptions::new().halt(halt),
+                    )
+                    .unwrap();
+                Arc::new(context)
+            }
+
+            fn halt(_: &Parameters, blackboard: &mut Blackboard, _: ()) {
+                let halted = blackboard.get::<u32>(&"halted".into()).map_or(0, |h| *h);
+                blackboard.set(&"halted".into(), halted + 1).unwrap();
+            }
+
+            fn chase_while_visible(tree: &BehaviourTree<()>) -> (Vec<TreeResult>, u32, u32) {
+                let mut state = TreeState::new(tree);
+                let blackboard = state.blackboard_mut();
+                blackboard.set(&"visible".into(), true).unwrap();
+                let mut results = vec![tree.tick(&mut state, ()), tree.tick(&mut state, ())];
+
+                state
+                    .blackboard_mut()
+                    .set(&"visible".into(), false)
+                    .unwrap();
+                results.push(tree.tick(&mut state, ()));
+                let chased = *state.blackboard().get::<u32>(&"chased".into()).unwrap();
+                let halted = state
+                    .blackboard()
+                    .get::<u32>(&"halted".into())
+                    .map_or(0, |h| *h);
+                (results, chased, halted)
+            }
+
+            #[test]
+            fn halts_executors_with_ports() {
+                let mut context = BehaviourContext::new();
+                context
+                    .register_condition(&"visible".into(), visible)
+                    .unwrap();
+                context
+                    .register_executor_with_options(
+                        &"chase".into(),
+                        chase,
+                        ExecutorOptions::new()
+                            .halt(halt)
+                            .ports([Port::input("speed", PortType::Float)]),
+                    )
+                    .unwrap();
+                let ctx: Arc<BehaviourContext<()>> = Arc::new(context);
+                let chase_with = |parameters| {
+                    Node::Root(Box::new(Node::ReactiveSequence {
+                        children: vec![
+                            Node::Condition("visible".into()),
+                            Node::Executor("chase".into(), parameters),
+                        ],
+                    }))
+                    .compile(Arc::downgrade(&ctx))
+                };
+
+                assert_eq!(
+                    chase_with(Parameters::new()).err(),
+                    Some(TreeCompilationError::MissingPort {
+                        node: "chase".into(),
+                        port: "speed".into()
+                    })
+                );
+                let tree = chase_with(Parameters::new().with("speed", 2.0)).unwrap();
+                let (_, chased, halted) = chase_while_visible(&tree);
+                assert_eq!(chased, 2);
+                assert_eq!(halted, 1);
+            }
+
+            #[test]
+            fn reactive_sequence() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::ReactiveSequence {
+                        children: vec![
+                            Node::Condition("visible".into()),
+                            Node::Executor("chase".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let (results, chased, halted) = chase_while_visible(&tree);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Failure
+                    ]
+                );
+                assert_eq!(chased, 2);
+                assert_eq!(halted, 1);
+            }
+
+            #[test]
+            fn halted_when_abandoned() {
+                let ctx = context();
+                let halted = |node: Node, ticks: usize| {
+                    let tree = compile(&ctx, node);
+                    let mut state = TreeState::new(&tree);
+                    let results: Vec<TreeResult> =
+                        (0..ticks).map(|_| tree.tick(&mut state, ())).collect();
+                    let halted = state
+                        .blackboard()
+                        .get::<u32>(&"halted".into())
+                        .map_or(0, |h| *h);
+                    (results.last().copied(), halted)
+                };
+
+                let parallel = Node::Parallel {
+                    policy: ParallelPolicy::RequireOne,
+                    children: vec![
+                        Node::Executor("chase".into(), Parameters::new()),
+                        Node::Inverter {
+                            child: Box::new(Node::Condition("visible".into())),
+                        },
+                    ],
+                };
+                assert_eq!(halted(parallel, 1), (Some(TreeResult::Success), 1));
+
+                let tree = compile(
+                    &ctx,
+                    Node::Timeout {
+                        duration: Duration::from_secs(1),
+                        child: Box::new(Node::Executor("chase".into(), Parameters::new())),
+                    },
+                );
+                let mut state = TreeState::new(&tree);
+                let mut clock = ManualClock::default();
+                assert_eq!(
+                    tree.tick_with_clock(&mut state, &clock, ()),
+                    TreeResult::Running
+                );
+                clock.advance(Duration::from_secs(1));
+                assert_eq!(
+                    tree.tick_with_clock(&mut state, &clock, ()),
+                    TreeResult::Failure
+                );
+                assert_eq!(state.blackboard().get::<u32>(&"halted".into()), Ok(&1));
+
+                let forced = Node::ForceFailure {
+                    child: Box::new(Node::Executor("chase".into(), Parameters::new())),
+                };
+                assert_eq!(halted(forced, 2), (Some(TreeResult::Running), 0));
+            }
+
+            #[test]
+            fn halt_tree() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Parallel {
+                        policy: ParallelPolicy::RequireAll,
+                        children: vec![
+                            Node::Executor("chase".into(), Parameters::new()),
+                            Node::Executor("chase".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let mut state = TreeState::new(&tree);
+
+                assert_eq!(tree.tick(&mut state, ()), TreeResult::Running);
+                tree.halt(&mut state, ());
+                assert!(!state.is_running());
+                assert_eq!(state.blackboard().get::<u32>(&"halted".into()), Ok(&2));
+            }
+
+            #[test]
+            fn sequence_is_not_reactive() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Sequence {
+                        children: vec![
+                            Node::Condition("visible".into()),
+                            Node::Executor("chase".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let (results, chased, halted) = chase_while_visible(&tree);
+                assert_eq!(results, vec![TreeResult::Running; 3]);
+                assert_eq!(chased, 3);
+                assert_eq!(halted, 0);
+            }
+
+            #[test]
+            fn reactive_fallback() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::ReactiveFallback {
+                        children: vec![
+                            Node::Inverter {
+                                child: Box::new(Node::Condition("visible".into())),
+                            },
+                            Node::Executor("chase".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let (results, chased, halted) = chase_while_visible(&tree);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Success
+                    ]
+                );
+                assert_eq!(chased, 2);
+                assert_eq!(halted, 1);
+            }
+        }
+
+        mod resume {
+            use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
+
+            use crate::{
+                blackboard::Blackboard,
+                clock::ManualClock,
+                context::BehaviourContext,
+                parameter::Parameters,
+                state::{TreeResult, TreeState},
+                tree::{BehaviourNode as Node, BehaviourTree, ParallelPolicy},
+            };
+
+            type Log = Rc<RefCell<Vec<&'static str>>>;
+
+            fn greet(_: &Parameters, _: &mut Blackboard, (log,): (Log,)) -> TreeResult {
+                log.borrow_mut().push("greet");
+                TreeResult::Success
+            }
+
+            fn walk(_: &Parameters, _: &mut Blackboard, (log,): (Log,)) -> TreeResult {
+                let mut log = log.borrow_mut();
+                let steps = log.iter().filter(|entry| **entry == "walk").count();
+                log.push("walk");
+                if steps % 3 < 2 {
+                    TreeResult::Running
+                } else {
+                    TreeResult::Success
+                }
+            }
+
+            fn flaky(_: &Parameters, _: &mut Blackboard, (log,): (Log,)) -> TreeResult {
+                let mut log = log.borrow_mut();
+                let attempts = log.iter().filter(|entry| **entry == "flaky").count();
+                log.push("flaky");
+                if attempts % 3 < 2 {
+                    TreeResult::Failure
+                } else {
+                    TreeResult::Success
+                }
+            }
+
+            fn idle(_: &Parameters, _: &mut Blackboard, (log,): (Log,)) -> TreeResult {
+                log.borrow_mut().push("idle");
+                TreeResult::Running
+            }
+
+            fn compile(ctx: &Arc<BehaviourContext<(Log,)>>, node: Node) -> BehaviourTree<(Log,)> {
+                Node::Root(Box::new(node))
+                    .compile(Arc::downgrade(ctx))
+                    .unwrap()
+            }
+
+            fn context() -> Arc<BehaviourContext<(Log,)>> {
+                let mut context = BehaviourContext::new();
+                context.register_executor(&"greet".into(), greet).unwrap();
+                context.register_executor(&"walk".into(), walk).unwrap();
+                context.register_executor(&"flaky".into(), flaky).unwrap();
+                context.register_executor(&"idle".into(), idle).unwrap();
+                Arc::new(context)
+            }
+
+            #[test]
+            fn sequence_resumes_running_child() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Sequence {
+                        children: vec![
+                            Node::Executor("greet".into(), Parameters::new()),
+                            Node::Executor("walk".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let log = Log::default();
+                let mut state = TreeState::new(&tree);
+
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert!(state.is_running());
+                assert_eq!(state.executions()[0].previous(), &vec![0]);
+                assert_eq!(state.executions()[0].position(), 4);
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Success);
+                assert!(!state.is_running());
+                assert_eq!(*log.borrow(), vec!["greet", "walk", "walk", "walk"]);
+
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(log.borrow()[4..], ["greet", "walk"]);
+            }
+
+            #[test]
+            fn parallel_resumes_running_children() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Parallel {
+                        policy: ParallelPolicy::RequireAll,
+                        children: vec![
+                            Node::Executor("walk".into(), Parameters::new()),
+                            Node::Executor("greet".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let log = Log::default();
+                let mut state = TreeState::new(&tree);
+
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Success);
+                assert_eq!(*log.borrow(), vec!["walk", "greet", "walk", "walk"]);
+            }
+
+            #[test]
+            fn parallel_threshold_across_ticks() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Parallel {
+                        policy: ParallelPolicy::Threshold {
+                            success: 2,
+                            failure: 2,
+                        },
+                        children: vec![
+                            Node::Executor("walk".into(), Parameters::new()),
+                            Node::Executor("walk".into(), Parameters::new()),
+                            Node::Executor("greet".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let log = Log::default();
+                let mut state = TreeState::new(&tree);
+
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(state.executions().len(), 2);
+                // The first walk completes on the second tick, which together
+                // with the earlier greet meets the threshold.
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Success);
+                assert!(!state.is_running());
+            }
+
+            fn tick_all(tree: &BehaviourTree<(Log,)>, ticks: usize) -> (Vec<TreeResult>, Log) {
+                let log = Log::default();
+                let mut state = TreeState::new(tree);
+                let results = (0..ticks)
+                    .map(|_| tree.tick(&mut state, (log.clone(),)))
+                    .collect();
+                (results, log)
+            }
+
+            #[test]
+            fn repeat() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Repeat {
+                        count: 3,
+                        child: Box::new(Node::Executor("greet".into(), Parameters::new())),
+                    },
+                );
+                let (results, log) = tick_all(&tree, 1);
+                assert_eq!(results, vec![TreeResult::Success]);
+                assert_eq!(log.borrow().len(), 3);
+
+                let tree = compile(
+                    &ctx,
+                    Node::Repeat {
+                        count: 2,
+                        child: Box::new(Node::Executor("walk".into(), Parameters::new())),
+                    },
+                );
+                let (results, _) = tick_all(&tree, 5);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Success
+                    ]
+                );
+
+                let tree = compile(
+                    &ctx,
+                    Node::Repeat {
+                        count: 2,
+                        child: Box::new(Node::Executor("flaky".into(), Parameters::new())),
+                    },
+                );
+                let (results, _) = tick_all(&tree, 1);
+                assert_eq!(results, vec![TreeResult::Failure]);
+            }
+
+            #[test]
+            fn retry_until_success() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::RetryUntilSuccess {
+                        attempts: 3,
+                        child: Box::new(Node::Executor("flaky".into(), Parameters::new())),
+                    },
+                );
+                let (results, log) = tick_all(&tree, 1);
+                assert_eq!(results, vec![TreeResult::Success]);
+                assert_eq!(log.borrow().len(), 3);
+
+                let tree = compile(
+                    &ctx,
+                    Node::RetryUntilSuccess {
+                        attempts: 2,
+                        child: Box::new(Node::Executor("flaky".into(), Parameters::new())),
+                    },
+                );
+                let (results, log) = tick_all(&tree, 1);
+                assert_eq!(results, vec![TreeResult::Failure]);
+                assert_eq!(log.borrow().len(), 2);
+            }
+
+            #[test]
+            fn until_failure() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Sequence {
+                        children: vec![
+                            Node::Executor("greet".into(), Parameters::new()),
+                            Node::UntilFailure {
+                                child: Box::new(Node::Inverter {
+                                    child: Box::new(Node::Executor(
+                                        "flaky".into(),
+                                        Parameters::new(),
+                                    )),
+                                }),
+                            },
+                        ],
+                    },
+                );
+                let (results, log) = tick_all(&tree, 3);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Failure
+                    ]
+                );
+                assert_eq!(*log.borrow(), vec!["greet", "flaky", "flaky", "flaky"]);
+            }
+
+            fn tick_at(tree: &BehaviourTree<(Log,)>, times: &[u64]) -> (Vec<TreeResult>, Log) {
+                let log = Log::default();
+                let mut state = TreeState::new(tree);
+                let mut clock = ManualClock::default();
+                let results = times
+                    .iter()
+                    .map(|&millis| {
+                        clock.set(Duration::from_millis(millis));
+                        tree.tick_with_clock(&mut state, &clock, (log.clone(),))
+                    })
+                    .collect();
+                (results, log)
+            }
+
+            #[test]
+            fn timeout() {
+                let ctx = context();
+                let node = Node::Timeout {
+                    duration: Duration::from_secs(2),
+                    child: Box::new(Node::Executor("idle".into(), Parameters::new())),
+                };
+                let tree = compile(&ctx, node);
+                let (results, log) = tick_at(&tree, &[0, 1000, 2000, 3000]);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Failure,
+                        TreeResult::Running
+                    ]
+                );
+                assert_eq!(log.borrow().len(), 3);
+            }
+
+            #[test]
+            fn cooldown() {
+                let ctx = context();
+                let node = Node::Cooldown {
+                    duration: Duration::from_secs(5),
+                    child: Box::new(Node::Executor("greet".into(), Parameters::new())),
+                };
+                let tree = compile(&ctx, node);
+                let (results, log) = tick_at(&tree, &[0, 1000, 4999, 5000]);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Success,
+                        TreeResult::Failure,
+                        TreeResult::Failure,
+                        TreeResult::Success
+                    ]
+                );
+                assert_eq!(log.borrow().len(), 2);
+            }
+
+            #[test]
+            fn delay() {
+                let ctx = context();
+                let node = Node::Sequence {
+                    children: vec![
+                        Node::Executor("greet".into(), Parameters::new()),
+                        Node::Delay {
+                            duration: Duration::from_secs(1),
+                            child: Box::new(Node::Executor("greet".into(), Parameters::new())),
+                        },
+                    ],
+                };
+                let tree = compile(&ctx, node);
+                let (results, log) = tick_at(&tree, &[0, 500, 1000]);
+                assert_eq!(
+                    results,
+                    vec![
+                        TreeResult::Running,
+                        TreeResult::Running,
+                        TreeResult::Success
+                    ]
+                );
+                assert_eq!(*log.borrow(), vec!["greet", "greet"]);
+            }
+
+            #[test]
+            fn agents_share_tree() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Sequence {
+                        children: vec![
+                            Node::Executor("greet".into(), Parameters::new()),
+                            Node::Executor("walk".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let first_log = Log::default();
+                let second_log = Log::default();
+                let mut first = TreeState::new(&tree);
+                let mut second = TreeState::new(&tree);
+
+                assert_eq!(
+                    tree.tick(&mut first, (first_log.clone(),)),
+                    TreeResult::Running
+                );
+                assert_eq!(
+                    tree.tick(&mut first, (first_log.clone(),)),
+                    TreeResult::Running
+                );
+                assert_eq!(
+                    tree.tick(&mut second, (second_log.clone(),)),
+                    TreeResult::Running
+                );
+                assert_eq!(
+                    tree.tick(&mut first, (first_log.clone(),)),
+                    TreeResult::Success
+                );
+                assert!(!first.is_running());
+                assert!(second.is_running());
+                assert_eq!(*second_log.borrow(), vec!["greet", "walk"]);
+            }
+
+            #[test]
+            #[should_panic(expected = "different tree")]
+            fn state_from_other_tree() {
+                let ctx = context();
+                let tree = compile(&ctx, Node::Executor("greet".into(), Parameters::new()));
+                let other = compile(
+                    &ctx,
+                    Node::Sequence {
+                        children: vec![Node::Executor("greet".into(), Parameters::new())],
+                    },
+                );
+
+                tree.tick(&mut TreeState::new(&other), (Log::default(),));
+            }
+
+            #[test]
+            fn completed_branches_are_abandoned() {
+                let ctx = context();
+                let tree = compile(
+                    &ctx,
+                    Node::Fallback {
+                        children: vec![
+                            Node::Parallel {
+                                policy: ParallelPolicy::RequireAll,
+                                children: vec![Node::Executor("walk".into(), Parameters::new())],
+                            },
+                            Node::Executor("greet".into(), Parameters::new()),
+                        ],
+                    },
+                );
+                let log = Log::default();
+                let mut state = TreeState::new(&tree);
+
+                assert_eq!(tree.tick(&mut state, (log.clone(),)), TreeResult::Running);
+                assert_eq!(state.executions()[0].previous(), &vec![0, 2]);
+                state.clear();
+                assert!(!state.is_running());
+            }
+        }
+    }
 }
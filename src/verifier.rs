@@ -0,0 +1,418 @@
+use std::fmt::Display;
+
+use crate::tree::{
+    opcode_name, BehaviourTree, CONDITION_ID, DECORATOR_ID, EXECUTOR_ID, FALLBACK_ID, ID_MASK,
+    NODE_SIZE, OPCODE_SHIFT, PARALLEL_FIELD_BITS, PARALLEL_FIELD_MASK, PARALLEL_ID,
+    REACTIVE_FALLBACK_ID, REACTIVE_SEQUENCE_ID, REPEAT_ID, RETRY_UNTIL_SUCCESS_ID, SEQUENCE_ID,
+};
+
+/// The most levels of nodes a tree can have, counting the root. Ticking and
+/// decompiling recurse once per level, so `compile` and `verify` reject deeper
+/// trees rather than let them overflow the stack.
+pub const MAX_DEPTH: usize = 1024;
+
+/// Why the code of a tree cannot be ticked safely, and the offset in `code` of
+/// the node at fault.
+#[derive(Debug, PartialEq)]
+pub struct VerificationError {
+    pub offset: usize,
+    pub kind: VerificationErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VerificationErrorKind {
+    /// The code is empty or ends part way through a node.
+    IncompleteNode,
+    /// The node has no entry in the constant pool.
+    MissingConstants,
+    UnknownOpcode(u8),
+    /// The operand is a count or parallel policy the compiler would reject.
+    InvalidOperand(u32),
+    /// The node's handle is to an executor that is not registered, either
+    /// because it never was or because it has since been removed or replaced.
+    UnknownExecutor(usize),
+    UnknownDecorator(usize),
+    UnknownCondition(usize),
+    /// A leaf node has a child offset.
+    UnexpectedChildOffset(usize),
+    /// The children of the node do not start on a node boundary, or run past
+    /// the end of the code.
+    InvalidChildOffset(usize),
+    /// The node is the child of more than one node, or of one of its own
+    /// descendants.
+    RevisitedNode,
+    /// The node cannot be reached from the root.
+    UnreachableNode,
+    /// The node is more than `limit` levels deep, counting the root.
+    TooDeep {
+        limit: usize,
+    },
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node at {}: {}", self.offset, self.kind)
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+impl Display for VerificationErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IncompleteNode => write!(f, "the code ends part way through a node"),
+            Self::MissingConstants => write!(f, "the node has no constants"),
+            Self::UnknownOpcode(opcode) => write!(f, "unknown opcode {opcode:#04x}"),
+            Self::InvalidOperand(operand) => write!(f, "invalid operand {operand}"),
+            Self::UnknownExecutor(index) => write!(f, "no executor is registered at {index}"),
+            Self::UnknownDecorator(index) => write!(f, "no decorator is registered at {index}"),
+            Self::UnknownCondition(index) => write!(f, "no condition is registered at {index}"),
+            Self::UnexpectedChildOffset(offset) => {
+                write!(f, "a leaf node has children at {offset}")
+            }
+            Self::InvalidChildOffset(offset) => {
+                write!(
+                    f,
+                    "the children at {offset} are not whole nodes in the code"
+                )
+            }
+            Self::RevisitedNode => write!(f, "the node is reached more than once from the root"),
+            Self::UnreachableNode => write!(f, "the node cannot be reached from the root"),
+            Self::TooDeep { limit } => write!(f, "the node is more than {limit} levels deep"),
+        }
+    }
+}
+
+impl std::error::Error for VerificationErrorKind {}
+
+impl<Calltype> BehaviourTree<Calltype> {
+    /// Checks that the code only holds known opcodes, refers to registry
+    /// entries that exist in the tree's context, and forms a single tree in
+    /// which every node is reached exactly once from the root and no more than
+    /// `MAX_DEPTH` levels below it.
+    ///
+    /// Compiled trees always pass, but code from any other source should be
+    /// verified before it is ticked.
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        let error = |offset: usize, kind| Err(VerificationError { offset, kind });
+        let node_count = self.code.len() / NODE_SIZE;
+        if self.code.is_empty() || !self.code.len().is_multiple_of(NODE_SIZE) {
+            return error(
+                node_count * NODE_SIZE,
+                VerificationErrorKind::IncompleteNode,
+            );
+        }
+
+        // The children of every node, as a range of positions in `code`.
+        let mut children = Vec::with_capacity(node_count);
+        for position in (0..self.code.len()).step_by(NODE_SIZE) {
+            match self.constant_indices.get(position / NODE_SIZE) {
+                Some(&index) if (index as usize) < self.constants.len() => {}
+                _ => return error(position, VerificationErrorKind::MissingConstants),
+            }
+            let word = self.code[position];
+            let opcode = (word >> OPCODE_SHIFT) as u8;
+            let operand = word & !ID_MASK;
+            let child_offset = self.code[position + 1] as usize;
+            if opcode_name(opcode).is_none() {
+                return error(position, VerificationErrorKind::UnknownOpcode(opcode));
+            }
+
+            let handle = self.handle(position);
+            let child_count = match opcode {
+                EXECUTOR_ID if self.context.executor_id(&handle).is_none() => {
+                    return error(
+                        position,
+                        VerificationErrorKind::UnknownExecutor(handle.value()),
+                    )
+                }
+                DECORATOR_ID if self.context.decorator_id(&handle).is_none() => {
+                    return error(
+                        position,
+                        VerificationErrorKind::UnknownDecorator(handle.value()),
+                    )
+                }
+                CONDITION_ID if self.context.condition_id(&handle).is_none() => {
+                    return error(
+                        position,
+                        VerificationErrorKind::UnknownCondition(handle.value()),
+                    )
+                }
+                EXECUTOR_ID | CONDITION_ID => 0,
+                PARALLEL_ID => {
+                    let count = operand & PARALLEL_FIELD_MASK;
+                    let failure = (operand >> PARALLEL_FIELD_BITS) & PARALLEL_FIELD_MASK;
+                    let success = operand >> (PARALLEL_FIELD_BITS * 2);
+                    if !(1..=count).contains(&success) || !(1..=count).contains(&failure) {
+                        return error(position, VerificationErrorKind::InvalidOperand(operand));
+                    }
+                    count as usize
+                }
+                REPEAT_ID | RETRY_UNTIL_SUCCESS_ID if operand == 0 => {
+                    return error(position, VerificationErrorKind::InvalidOperand(operand))
+                }
+                SEQUENCE_ID | FALLBACK_ID | REACTIVE_SEQUENCE_ID | REACTIVE_FALLBACK_ID => {
+                    if operand == 0 {
+                        return error(position, VerificationErrorKind::InvalidOperand(operand));
+                    }
+                    operand as usize
+                }
+                _ => 1,
+            };
+
+            if child_count == 0 {
+                if child_offset != 0 {
+                    return error(
+                        position,
+                        VerificationErrorKind::UnexpectedChildOffset(child_offset),
+                    );
+                }
+            } else if !child_offset.is_multiple_of(NODE_SIZE)
+                || child_offset + child_count * NODE_SIZE > self.code.len()
+            {
+                return error(
+                    position,
+                    VerificationErrorKind::InvalidChildOffset(child_offset),
+                );
+            }
+            children.push(child_offset..child_offset + child_count * NODE_SIZE);
+        }
+
+        // Walking down from the root, a node that is reached twice either has
+        // two parents or lies on a cycle.
+        let mut visited = vec![false; node_count];
+        let mut pending = vec![(0, 1)];
+        while let Some((position, depth)) = pending.pop() {
+            if std::mem::replace(&mut visited[position / NODE_SIZE], true) {
+                return error(position, VerificationErrorKind::RevisitedNode);
+            }
+            if depth > MAX_DEPTH {
+                return error(
+                    position,
+                    VerificationErrorKind::TooDeep { limit: MAX_DEPTH },
+                );
+            }
+            pending.extend(
+                children[position / NODE_SIZE]
+                    .clone()
+                    .step_by(NODE_SIZE)
+                    .map(|child| (child, depth + 1)),
+            );
+        }
+        match visited.iter().position(|&visited| !visited) {
+            Some(node) => error(node * NODE_SIZE, VerificationErrorKind::UnreachableNode),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        blackboard::Blackboard,
+        bytecode::BytecodeError,
+        context::BehaviourContext,
+        parameter::Parameters,
+        state::{TreeResult, TreeState},
+        tree::{BehaviourNode, BehaviourTree, INVERTER_ID, NODE_SIZE, OPCODE_SHIFT},
+    };
+
+    use super::{VerificationError, VerificationErrorKind as Kind, MAX_DEPTH};
+
+    fn succeed(_: &Parameters, _: &mut Blackboard, _: ()) -> TreeResult {
+        TreeResult::Success
+    }
+
+    fn keep(result: TreeResult, _: &Parameters, _: ()) -> TreeResult {
+        result
+    }
+
+    fn ready(_: &Blackboard, _: ()) -> bool {
+        true
+    }
+
+    fn context() -> Arc<BehaviourContext<()>> {
+        let mut context = BehaviourContext::new();
+        context.register_executor(&"idle".into(), succeed).unwrap();
+        context.register_decorator(&"keep".into(), keep).unwrap();
+        context.register_condition(&"ready".into(), ready).unwrap();
+        Arc::new(context)
+    }
+
+    /// A sequence at 0 whose children are an inverter at 2 and an executor at
+    /// 4, with the inverter's child, a condition, at 6.
+    fn tree(ctx: &Arc<BehaviourContext<()>>) -> BehaviourTree<()> {
+        BehaviourNode::from_dsl("sequence { invert { cond:ready }; act:idle }")
+            .unwrap()
+            .compile(Arc::downgrade(ctx))
+            .unwrap()
+    }
+
+    /// A chain of inverters ending in a condition, `levels` nodes deep.
+    fn chain(ctx: &Arc<BehaviourContext<()>>, levels: usize) -> BehaviourTree<()> {
+        let mut subject = tree(ctx);
+        let (condition, generation) = (subject.code[6], subject.handle_generations[3]);
+        subject.code.clear();
+        for level in 1..levels {
+            subject.code.push((INVERTER_ID as u32) << OPCODE_SHIFT);
+            subject.code.push((level * NODE_SIZE) as u32);
+        }
+        subject.code.extend([condition, 0]);
+        subject.constant_indices = vec![0; levels];
+        subject.handle_generations = vec![0; levels];
+        subject.handle_generations[levels - 1] = generation;
+        subject.node_count = levels;
+        subject
+    }
+
+    fn error(offset: usize, kind: Kind) -> Result<(), VerificationError> {
+        Err(VerificationError { offset, kind })
+    }
+
+    #[test]
+    fn accepts_compiled_trees() {
+        let ctx = context();
+        let tree = BehaviourNode::from_dsl(
+            r#"reactive_fallback {
+                parallel(success = 2, failure = 1) {
+                    repeat(3) { act:idle }
+                    retry(2) { decorate:keep(label = "guard") { cond:ready } }
+                }
+                sequence { timeout(10ms) { act:idle }; until_failure { cond:ready } }
+            }"#,
+        )
+        .unwrap()
+        .compile(Arc::downgrade(&ctx))
+        .unwrap();
+
+        assert_eq!(tree.verify(), Ok(()));
+        assert_eq!(self::tree(&ctx).verify(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_bad_nodes() {
+        let ctx = context();
+
+        let mut subject = tree(&ctx);
+        subject.code[4] = 0x7F << OPCODE_SHIFT;
+        assert_eq!(subject.verify(), error(4, Kind::UnknownOpcode(0x7F)));
+
+        let mut subject = tree(&ctx);
+        subject.code[4] += 1;
+        assert_eq!(subject.verify(), error(4, Kind::UnknownExecutor(1)));
+
+        let mut subject = tree(&ctx);
+        subject.code[6] += 3;
+        assert_eq!(subject.verify(), error(6, Kind::UnknownCondition(3)));
+
+        // A handle issued before the executor was replaced.
+        let mut subject = tree(&ctx);
+        subject.handle_generations[2] += 1;
+        assert_eq!(subject.verify(), error(4, Kind::UnknownExecutor(0)));
+
+        let mut subject = tree(&ctx);
+        subject.code[0] &= !0xFF;
+        assert_eq!(subject.verify(), error(0, Kind::InvalidOperand(0)));
+
+        let mut subject = tree(&ctx);
+        subject.constant_indices[3] = subject.constants.len() as u32;
+        assert_eq!(subject.verify(), error(6, Kind::MissingConstants));
+
+        let mut subject = tree(&ctx);
+        subject.code.pop();
+        assert_eq!(subject.verify(), error(6, Kind::IncompleteNode));
+    }
+
+    #[test]
+    fn rejects_bad_child_offsets() {
+        let ctx = context();
+
+        let mut subject = tree(&ctx);
+        subject.code[5] = 2;
+        assert_eq!(subject.verify(), error(4, Kind::UnexpectedChildOffset(2)));
+
+        let mut subject = tree(&ctx);
+        subject.code[3] = 5;
+        assert_eq!(subject.verify(), error(2, Kind::InvalidChildOffset(5)));
+
+        let mut subject = tree(&ctx);
+        subject.code[1] = 6;
+        assert_eq!(subject.verify(), error(0, Kind::InvalidChildOffset(6)));
+    }
+
+    #[test]
+    fn rejects_graphs_that_are_not_trees() {
+        let ctx = context();
+
+        // The inverter's child is the root.
+        let mut subject = tree(&ctx);
+        subject.code[3] = 0;
+        assert_eq!(subject.verify(), error(0, Kind::RevisitedNode));
+
+        // The inverter and the executor share a child.
+        let mut subject = tree(&ctx);
+        subject.code[4] = subject.code[2];
+        subject.code[5] = subject.code[3];
+        assert_eq!(subject.verify(), error(6, Kind::RevisitedNode));
+
+        // The sequence no longer counts the executor among its children.
+        let mut subject = tree(&ctx);
+        subject.code[0] -= 1;
+        assert_eq!(subject.verify(), error(4, Kind::UnreachableNode));
+    }
+
+    #[test]
+    fn rejects_deep_trees() {
+        let ctx = context();
+
+        let subject = chain(&ctx, MAX_DEPTH);
+        assert_eq!(subject.verify(), Ok(()));
+        let mut state = TreeState::new(&subject);
+        // An odd number of inverters above a condition that holds.
+        assert_eq!(subject.tick(&mut state, ()), TreeResult::Failure);
+        assert!(subject.decompile().is_ok());
+
+        let subject = chain(&ctx, 50_000);
+        assert_eq!(
+            subject.verify(),
+            error(MAX_DEPTH * NODE_SIZE, Kind::TooDeep { limit: MAX_DEPTH })
+        );
+        assert!(subject.decompile().is_err());
+    }
+
+    #[test]
+    fn describes_errors() {
+        let ctx = context();
+        let mut subject = tree(&ctx);
+        subject.code[4] = 0x7F << OPCODE_SHIFT;
+
+        assert_eq!(
+            subject.verify().unwrap_err().to_string(),
+            "node at 4: unknown opcode 0x7f"
+        );
+        assert_eq!(
+            Kind::InvalidChildOffset(5).to_string(),
+            "the children at 5 are not whole nodes in the code"
+        );
+    }
+
+    #[test]
+    fn verifies_loaded_trees() {
+        let ctx = context();
+        let subject = tree(&ctx);
+        let mut bytes = subject.to_bytes().unwrap();
+        // The code is last, with the opcode in the high byte of each word.
+        let code = bytes.len() - subject.code().len() * 4;
+        bytes[code + NODE_SIZE * 4 * 2 + 3] = 0x7F;
+
+        assert_eq!(
+            BehaviourTree::from_bytes(&bytes, Arc::downgrade(&ctx)).err(),
+            Some(BytecodeError::InvalidCode(VerificationError {
+                offset: 4,
+                kind: Kind::UnknownOpcode(0x7F)
+            }))
+        );
+    }
+}
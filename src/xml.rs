@@ -0,0 +1,1085 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+use quick_xml::{escape::escape, events::BytesStart, events::Event, Reader, XmlVersion};
+
+use crate::{
+    identifier::Identifier,
+    parameter::{Parameter, Parameters},
+    tree::{BehaviourNode, ParallelPolicy},
+};
+
+const MAIN_TREE: &str = "MainTree";
+const INDENT: &str = "    ";
+
+/// BehaviorTree.CPP nodes with no equivalent here, from both version 4 and
+/// the version 3 names it has since dropped, reported as unsupported rather
+/// than mistaken for registered actions or decorators.
+const UNSUPPORTED_BUILTINS: &[&str] = &[
+    "AlwaysFailure",
+    "AlwaysSuccess",
+    "AsyncFallback",
+    "AsyncSequence",
+    "BlackboardCheckBool",
+    "BlackboardCheckDouble",
+    "BlackboardCheckInt",
+    "BlackboardCheckString",
+    "ConsumeQueue",
+    "IfThenElse",
+    "LoopBool",
+    "LoopDouble",
+    "LoopInt",
+    "LoopString",
+    "ManualSelector",
+    "ParallelAll",
+    "Precondition",
+    "RetryUntilSuccesful",
+    "RunOnce",
+    "Script",
+    "ScriptCondition",
+    "SequenceStar",
+    "SequenceWithMemory",
+    "SetBlackboard",
+    "SkipUnlessUpdated",
+    "Sleep",
+    "SubTreePlus",
+    "Switch2",
+    "Switch3",
+    "Switch4",
+    "Switch5",
+    "Switch6",
+    "UnsetBlackboard",
+    "WaitValueUpdate",
+    "WasEntryUpdated",
+    "WhileDoElse",
+];
+
+/// Why a BehaviorTree.CPP document could not be imported, and where in the
+/// source the problem was found. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError {
+    line: usize,
+    column: usize,
+    kind: XmlErrorKind,
+}
+
+impl XmlError {
+    fn at(source: &str, offset: usize, kind: XmlErrorKind) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn kind(&self) -> &XmlErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for XmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlErrorKind {
+    /// The document is not well formed XML.
+    Malformed(String),
+    /// An element with no equivalent in this crate, such as `<Switch2>`.
+    UnsupportedElement(String),
+    UnsupportedAttribute {
+        element: String,
+        attribute: String,
+    },
+    MissingAttribute {
+        element: String,
+        attribute: String,
+    },
+    InvalidAttribute {
+        element: String,
+        attribute: String,
+        value: String,
+    },
+    /// A node has the wrong number of children for its kind.
+    InvalidChildCount {
+        element: String,
+        count: usize,
+    },
+    /// The document has no `<BehaviorTree>` of this ID.
+    UnknownTree(String),
+    /// A `<SubTree>` includes a tree it is itself part of.
+    RecursiveSubTree(String),
+}
+
+impl Display for XmlErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(message) => write!(f, "malformed XML: {message}"),
+            Self::UnsupportedElement(element) => write!(f, "<{element}> is not supported"),
+            Self::UnsupportedAttribute { element, attribute } => {
+                write!(
+                    f,
+                    "<{element}> does not support the attribute `{attribute}`"
+                )
+            }
+            Self::MissingAttribute { element, attribute } => {
+                write!(f, "<{element}> is missing the attribute `{attribute}`")
+            }
+            Self::InvalidAttribute {
+                element,
+                attribute,
+                value,
+            } => write!(f, "<{element}> has an invalid `{attribute}` of \"{value}\""),
+            Self::InvalidChildCount { element, count } => {
+                write!(f, "<{element}> cannot have {count} children")
+            }
+            Self::UnknownTree(id) => write!(f, "there is no <BehaviorTree> with the ID \"{id}\""),
+            Self::RecursiveSubTree(id) => write!(f, "the tree \"{id}\" includes itself"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlExportError {
+    /// A node BehaviorTree.CPP has no equivalent for, such as `Cooldown`.
+    UnsupportedNode(&'static str),
+    /// A parameter that would not import as the same value, such as an
+    /// `Identifier`, which BehaviorTree.CPP cannot tell apart from text.
+    UnsupportedParameter {
+        node: Identifier,
+        parameter: String,
+    },
+    RootNodeInTree,
+}
+
+impl Display for XmlExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedNode(node) => {
+                write!(f, "{node} nodes have no BehaviorTree.CPP equivalent")
+            }
+            Self::UnsupportedParameter { node, parameter } => write!(
+                f,
+                "the parameter `{parameter}` of `{node}` would not import as the same value"
+            ),
+            Self::RootNodeInTree => write!(f, "a Root node can only be at the top of a tree"),
+        }
+    }
+}
+
+impl std::error::Error for XmlExportError {}
+
+/// An element of the parsed document, with its offset into the source.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    offset: usize,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ModelKind {
+    Action,
+    Condition,
+    Decorator,
+}
+
+struct Importer<'a> {
+    source: &'a str,
+    trees: HashMap<&'a str, &'a Element>,
+    /// The kinds of custom nodes declared in `<TreeNodesModel>`, used to
+    /// resolve elements written in the compact `<MyAction/>` form.
+    models: HashMap<&'a str, ModelKind>,
+    /// The subtrees currently being expanded, to catch recursion.
+    expanding: Vec<&'a str>,
+}
+
+impl<'a> Importer<'a> {
+    fn error(&self, element: &Element, kind: XmlErrorKind) -> XmlError {
+        XmlError::at(self.source, element.offset, kind)
+    }
+
+    fn check_attributes(&self, element: &Element, allowed: &[&str]) -> Result<(), XmlError> {
+        match element
+            .attributes
+            .iter()
+            .find(|(key, _)| key != "name" && !allowed.contains(&key.as_str()))
+        {
+            Some((key, _)) => Err(self.error(
+                element,
+                XmlErrorKind::UnsupportedAttribute {
+                    element: element.name.clone(),
+                    attribute: key.clone(),
+                },
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn required<'e>(&self, element: &'e Element, attribute: &str) -> Result<&'e str, XmlError> {
+        element.attribute(attribute).ok_or_else(|| {
+            self.error(
+                element,
+                XmlErrorKind::MissingAttribute {
+                    element: element.name.clone(),
+                    attribute: attribute.into(),
+                },
+            )
+        })
+    }
+
+    fn number<T: FromStr>(
+        &self,
+        element: &Element,
+        attribute: &str,
+        default: Option<T>,
+    ) -> Result<T, XmlError> {
+        let value = match (element.attribute(attribute), default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => return Ok(default),
+            (None, None) => self.required(element, attribute)?,
+        };
+        value.parse().map_err(|_| {
+            self.error(
+                element,
+                XmlErrorKind::InvalidAttribute {
+                    element: element.name.clone(),
+                    attribute: attribute.into(),
+                    value: value.into(),
+                },
+            )
+        })
+    }
+
+    fn duration(&self, element: &Element, attribute: &str) -> Result<Duration, XmlError> {
+        Ok(Duration::from_millis(
+            self.number(element, attribute, None)?,
+        ))
+    }
+
+    fn children(
+        &mut self,
+        element: &'a Element,
+        remap: &HashMap<Identifier, Parameter>,
+    ) -> Result<Vec<BehaviourNode>, XmlError> {
+        element
+            .children
+            .iter()
+            .map(|child| self.node(child, remap))
+            .collect()
+    }
+
+    fn child(
+        &mut self,
+        element: &'a Element,
+        remap: &HashMap<Identifier, Parameter>,
+    ) -> Result<Box<BehaviourNode>, XmlError> {
+        match element.children.as_slice() {
+            [child] => Ok(Box::new(self.node(child, remap)?)),
+            children => Err(self.error(
+                element,
+                XmlErrorKind::InvalidChildCount {
+                    element: element.name.clone(),
+                    count: children.len(),
+                },
+            )),
+        }
+    }
+
+    fn leaf(&self, element: &Element) -> Result<(), XmlError> {
+        match element.children.len() {
+            0 => Ok(()),
+            count => Err(self.error(
+                element,
+                XmlErrorKind::InvalidChildCount {
+                    element: element.name.clone(),
+                    count,
+                },
+            )),
+        }
+    }
+
+    /// The attributes of a custom node other than its ID, as parameters.
+    fn parameters(&self, element: &Element, remap: &HashMap<Identifier, Parameter>) -> Parameters {
+        let mut parameters = Parameters::new();
+        for (name, value) in &element.attributes {
+            if name != "ID" && name != "name" {
+                parameters.insert(name, remapped(import_parameter(name, value), remap));
+            }
+        }
+        parameters
+    }
+
+    fn tree(
+        &mut self,
+        element: &Element,
+        id: &'a str,
+        remap: &HashMap<Identifier, Parameter>,
+    ) -> Result<BehaviourNode, XmlError> {
+        let Some(&tree) = self.trees.get(id) else {
+            return Err(self.error(element, XmlErrorKind::UnknownTree(id.into())));
+        };
+        if self.expanding.contains(&id) {
+            return Err(self.error(element, XmlErrorKind::RecursiveSubTree(id.into())));
+        }
+        self.expanding.push(id);
+        let node = self.child(tree, remap)?;
+        self.expanding.pop();
+        Ok(*node)
+    }
+
+    fn node(
+        &mut self,
+        element: &'a Element,
+        remap: &HashMap<Identifier, Parameter>,
+    ) -> Result<BehaviourNode, XmlError> {
+        let name = element.name.as_str();
+        let kind = match name {
+            "Action" | "Condition" | "Decorator" => None,
+            name => self.models.get(name).copied(),
+        };
+        let node = match (name, kind) {
+            ("Sequence", None) => BehaviourNode::Sequence {
+                children: self.children(element, remap)?,
+            },
+            ("Fallback", None) => BehaviourNode::Fallback {
+                children: self.children(element, remap)?,
+            },
+            ("ReactiveSequence", None) => BehaviourNode::ReactiveSequence {
+                children: self.children(element, remap)?,
+            },
+            ("ReactiveFallback", None) => BehaviourNode::ReactiveFallback {
+                children: self.children(element, remap)?,
+            },
+            ("Parallel", None) => {
+                self.check_attributes(element, &["success_count", "failure_count"])?;
+                // BehaviorTree.CPP counts -1 as every child.
+                let success: i64 = self.number(element, "success_count", Some(-1))?;
+                let failure: i64 = self.number(element, "failure_count", Some(1))?;
+                let children = self.children(element, remap)?;
+                let count = |value: i64| match value {
+                    -1 => Ok(children.len()),
+                    value => usize::try_from(value),
+                };
+                let policy = match (success, failure) {
+                    (-1, 1) => ParallelPolicy::RequireAll,
+                    (1, -1) => ParallelPolicy::RequireOne,
+                    _ => match (count(success), count(failure)) {
+                        (Ok(success), Ok(failure)) => {
+                            ParallelPolicy::Threshold { success, failure }
+                        }
+                        (success_count, _) => {
+                            let (attribute, value) = match success_count {
+                                Err(_) => ("success_count", success),
+                                Ok(_) => ("failure_count", failure),
+                            };
+                            return Err(self.error(
+                                element,
+                                XmlErrorKind::InvalidAttribute {
+                                    element: element.name.clone(),
+                                    attribute: attribute.into(),
+                                    value: value.to_string(),
+                                },
+                            ));
+                        }
+                    },
+                };
+                BehaviourNode::Parallel { policy, children }
+            }
+            ("Inverter", None) => BehaviourNode::Inverter {
+                child: self.child(element, remap)?,
+            },
+            ("ForceSuccess", None) => BehaviourNode::ForceSuccess {
+                child: self.child(element, remap)?,
+            },
+            ("ForceFailure", None) => BehaviourNode::ForceFailure {
+                child: self.child(element, remap)?,
+            },
+            ("KeepRunningUntilFailure", None) => BehaviourNode::UntilFailure {
+                child: self.child(element, remap)?,
+            },
+            ("Repeat", None) => {
+                self.check_attributes(element, &["num_cycles"])?;
+                BehaviourNode::Repeat {
+                    count: self.number(element, "num_cycles", None)?,
+                    child: self.child(element, remap)?,
+                }
+            }
+            ("RetryUntilSuccessful", None) => {
+                self.check_attributes(element, &["num_attempts"])?;
+                BehaviourNode::RetryUntilSuccess {
+                    attempts: self.number(element, "num_attempts", None)?,
+                    child: self.child(element, remap)?,
+                }
+            }
+            ("Timeout", None) => {
+                self.check_attributes(element, &["msec"])?;
+                BehaviourNode::Timeout {
+                    duration: self.duration(element, "msec")?,
+                    child: self.child(element, remap)?,
+                }
+            }
+            ("Delay", None) => {
+                self.check_attributes(element, &["delay_msec"])?;
+                BehaviourNode::Delay {
+                    duration: self.duration(element, "delay_msec")?,
+                    child: self.child(element, remap)?,
+                }
+            }
+            ("SubTree", None) => {
+                self.leaf(element)?;
+                let id = self.required(element, "ID")?;
+                // Subtrees are inlined and share the agent's blackboard, with
+                // their ports remapped onto the keys or constants given here.
+                let mut inner = remap.clone();
+                for (name, value) in &element.attributes {
+                    if !matches!(name.as_str(), "ID" | "name" | "_autoremap") {
+                        let parameter = remapped(import_parameter(name, value), remap);
+                        inner.insert(name.as_str().into(), parameter);
+                    }
+                }
+                self.tree(element, id, &inner)?
+            }
+            ("Action", _) | (_, Some(ModelKind::Action)) => {
+                self.leaf(element)?;
+                let id = match kind {
+                    Some(_) => name,
+                    None => self.required(element, "ID")?,
+                };
+                BehaviourNode::Executor(id.into(), self.parameters(element, remap))
+            }
+            ("Condition", _) | (_, Some(ModelKind::Condition)) => {
+                self.leaf(element)?;
+                self.check_attributes(element, &["ID"])?;
+                let id = match kind {
+                    Some(_) => name,
+                    None => self.required(element, "ID")?,
+                };
+                BehaviourNode::Condition(id.into())
+            }
+            ("Decorator", _) | (_, Some(ModelKind::Decorator)) => {
+                let id = match kind {
+                    Some(_) => name,
+                    None => self.required(element, "ID")?,
+                };
+                BehaviourNode::Decorator {
+                    name: id.into(),
+                    parameters: self.parameters(element, remap),
+                    child: self.child(element, remap)?,
+                }
+            }
+            (name, None) if !UNSUPPORTED_BUILTINS.contains(&name) => {
+                // Without a model, a custom node in the compact form is taken
+                // to be an action or decorator by its number of children.
+                match element.children.len() {
+                    0 => self.models.insert(name, ModelKind::Action),
+                    1 => self.models.insert(name, ModelKind::Decorator),
+                    _ => {
+                        return Err(
+                            self.error(element, XmlErrorKind::UnsupportedElement(name.into()))
+                        )
+                    }
+                };
+                return self.node(element, remap);
+            }
+            (name, _) => {
+                return Err(self.error(element, XmlErrorKind::UnsupportedElement(name.into())))
+            }
+        };
+        if let BehaviourNode::Sequence { .. }
+        | BehaviourNode::Fallback { .. }
+        | BehaviourNode::ReactiveSequence { .. }
+        | BehaviourNode::ReactiveFallback { .. }
+        | BehaviourNode::Inverter { .. }
+        | BehaviourNode::ForceSuccess { .. }
+        | BehaviourNode::ForceFailure { .. }
+        | BehaviourNode::UntilFailure { .. } = node
+        {
+            self.check_attributes(element, &[])?;
+        }
+        Ok(node)
+    }
+}
+
+/// Reads an attribute value as a parameter. `{key}` refers to a blackboard
+/// entry, with `{=}` using the port's own name as the key, and numbers are
+/// read as integers or floats. Anything else is text.
+fn import_parameter(name: &str, value: &str) -> Parameter {
+    if let Some(key) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+        return Parameter::BlackboardKey(if key == "=" { name } else { key }.into());
+    }
+    if let Ok(integer) = value.parse() {
+        return Parameter::Integer(integer);
+    }
+    match value.parse() {
+        Ok(float) if value.bytes().any(|b| b.is_ascii_digit()) => Parameter::Float(float),
+        _ => Parameter::Text(value.into()),
+    }
+}
+
+fn remapped(parameter: Parameter, remap: &HashMap<Identifier, Parameter>) -> Parameter {
+    match &parameter {
+        Parameter::BlackboardKey(key) => remap.get(key).cloned().unwrap_or(parameter),
+        _ => parameter,
+    }
+}
+
+fn parse(source: &str) -> Result<Element, XmlError> {
+    let mut reader = Reader::from_str(source);
+    reader.config_mut().trim_text(true);
+    let malformed = |reader: &Reader<&[u8]>, message: String| {
+        XmlError::at(
+            source,
+            reader.error_position() as usize,
+            XmlErrorKind::Malformed(message),
+        )
+    };
+    let element = |reader: &Reader<&[u8]>, start: &BytesStart, offset: usize| {
+        let mut attributes = Vec::new();
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(|e| malformed(reader, e.to_string()))?;
+            let value = attribute
+                .normalized_value(XmlVersion::Implicit1_0)
+                .map_err(|e| malformed(reader, e.to_string()))?;
+            let key = attribute.key.as_ref().to_string();
+            attributes.push((key, value.into_owned()));
+        }
+        Ok(Element {
+            name: start.name().as_ref().to_string(),
+            attributes,
+            children: Vec::new(),
+            offset,
+        })
+    };
+
+    let mut stack: Vec<Element> = Vec::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| malformed(&reader, e.to_string()))?;
+        let position = reader.buffer_position() as usize;
+        let finished = match event {
+            Event::Start(start) => {
+                let offset = position - start.len() - "<>".len();
+                stack.push(element(&reader, &start, offset)?);
+                None
+            }
+            Event::Empty(start) => Some(element(
+                &reader,
+                &start,
+                position - start.len() - "</>".len(),
+            )?),
+            Event::End(_) => stack.pop(),
+            Event::Eof => {
+                return Err(XmlError::at(
+                    source,
+                    position,
+                    XmlErrorKind::Malformed("unexpected end of document".into()),
+                ))
+            }
+            _ => None,
+        };
+        if let Some(finished) = finished {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(finished),
+                None => return Ok(finished),
+            }
+        }
+    }
+}
+
+impl BehaviourNode {
+    /// Imports the main tree of a BehaviorTree.CPP XML document, as written by
+    /// Groot. Subtrees are inlined, with their remapped ports substituted.
+    pub fn from_xml(source: &str) -> Result<Self, XmlError> {
+        let root = parse(source)?;
+        let mut importer = Importer {
+            source,
+            trees: HashMap::new(),
+            models: HashMap::new(),
+            expanding: Vec::new(),
+        };
+        if root.name != "root" {
+            return Err(importer.error(&root, XmlErrorKind::UnsupportedElement(root.name.clone())));
+        }
+        importer.check_attributes(&root, &["BTCPP_format", "main_tree_to_execute"])?;
+
+        for element in &root.children {
+            match element.name.as_str() {
+                "BehaviorTree" => {
+                    let id = importer.required(element, "ID")?;
+                    importer.trees.insert(id, element);
+                }
+                "TreeNodesModel" => {
+                    for model in &element.children {
+                        let kind = match model.name.as_str() {
+                            "Action" => ModelKind::Action,
+                            "Condition" => ModelKind::Condition,
+                            "Decorator" => ModelKind::Decorator,
+                            _ => continue,
+                        };
+                        importer
+                            .models
+                            .insert(importer.required(model, "ID")?, kind);
+                    }
+                }
+                name => {
+                    return Err(
+                        importer.error(element, XmlErrorKind::UnsupportedElement(name.into()))
+                    )
+                }
+            }
+        }
+
+        let main = match root.attribute("main_tree_to_execute") {
+            Some(main) => main,
+            None if importer.trees.len() == 1 => importer.trees.keys().next().unwrap(),
+            None => importer.required(&root, "main_tree_to_execute")?,
+        };
+        let tree = importer.tree(&root, main, &HashMap::new())?;
+        Ok(BehaviourNode::Root(Box::new(tree)))
+    }
+
+    /// Exports the tree as a BehaviorTree.CPP XML document with a single
+    /// `MainTree`, using the explicit `<Action ID="...">` form for custom nodes.
+    pub fn to_xml(&self) -> Result<String, XmlExportError> {
+        let node = match self {
+            BehaviourNode::Root(child) => child.as_ref(),
+            node => node,
+        };
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(&format!(
+            "<root BTCPP_format=\"4\" main_tree_to_execute=\"{MAIN_TREE}\">\n"
+        ));
+        out.push_str(&format!("{INDENT}<BehaviorTree ID=\"{MAIN_TREE}\">\n"));
+        export_node(node, 2, &mut out)?;
+        out.push_str(&format!("{INDENT}</BehaviorTree>\n</root>\n"));
+        Ok(out)
+    }
+}
+
+fn export_parameters(
+    id: &Identifier,
+    parameters: &Parameters,
+    attributes: &mut Vec<(String, String)>,
+) -> Result<(), XmlExportError> {
+    for (name, parameter) in parameters.iter() {
+        let value = match parameter {
+            Parameter::Integer(value) => value.to_string(),
+            Parameter::Float(value) => format!("{value:?}"),
+            Parameter::Text(value) => value.clone(),
+            Parameter::Identifier(value) => value.clone().into(),
+            Parameter::BlackboardKey(key) => format!("{{{}}}", String::from(key.clone())),
+        };
+        if import_parameter(name, &value) != *parameter {
+            return Err(XmlExportError::UnsupportedParameter {
+                node: id.clone(),
+                parameter: name.into(),
+            });
+        }
+        attributes.push((name.into(), value));
+    }
+    Ok(())
+}
+
+fn export_node(node: &BehaviourNode, depth: usize, out: &mut String) -> Result<(), XmlExportError> {
+    let millis = |duration: &Duration| duration.as_millis().to_string();
+    let mut attributes: Vec<(String, String)> = Vec::new();
+    let (name, children): (&str, Vec<&BehaviourNode>) = match node {
+        BehaviourNode::Root(_) => return Err(XmlExportError::RootNodeInTree),
+        BehaviourNode::Sequence { children } => ("Sequence", children.iter().collect()),
+        BehaviourNode::Fallback { children } => ("Fallback", children.iter().collect()),
+        BehaviourNode::ReactiveSequence { children } => {
+            ("ReactiveSequence", children.iter().collect())
+        }
+        BehaviourNode::ReactiveFallback { children } => {
+            ("ReactiveFallback", children.iter().collect())
+        }
+        BehaviourNode::Parallel { policy, children } => {
+            let (success, failure) = match *policy {
+                ParallelPolicy::RequireAll => (-1, 1),
+                ParallelPolicy::RequireOne => (1, -1),
+                ParallelPolicy::Threshold { success, failure } => (success as i64, failure as i64),
+            };
+            attributes.push(("success_count".into(), success.to_string()));
+            attributes.push(("failure_count".into(), failure.to_string()));
+            ("Parallel", children.iter().collect())
+        }
+        BehaviourNode::Decorator {
+            name,
+            parameters,
+            child,
+        } => {
+            attributes.push(("ID".into(), name.clone().into()));
+            export_parameters(name, parameters, &mut attributes)?;
+            ("Decorator", vec![child])
+        }
+        BehaviourNode::Executor(id, parameters) => {
+            attributes.push(("ID".into(), id.clone().into()));
+            export_parameters(id, parameters, &mut attributes)?;
+            ("Action", Vec::new())
+        }
+        BehaviourNode::Condition(id) => {
+            attributes.push(("ID".into(), id.clone().into()));
+            ("Condition", Vec::new())
+        }
+        BehaviourNode::Inverter { child } => ("Inverter", vec![child]),
+        BehaviourNode::ForceSuccess { child } => ("ForceSuccess", vec![child]),
+        BehaviourNode::ForceFailure { child } => ("ForceFailure", vec![child]),
+        BehaviourNode::Repeat { count, child } => {
+            attributes.push(("num_cycles".into(), count.to_string()));
+            ("Repeat", vec![child])
+        }
+        BehaviourNode::RetryUntilSuccess { attempts, child } => {
+            attributes.push(("num_attempts".into(), attempts.to_string()));
+            ("RetryUntilSuccessful", vec![child])
+        }
+        BehaviourNode::UntilFailure { child } => ("KeepRunningUntilFailure", vec![child]),
+        BehaviourNode::Timeout { duration, child } => {
+            attributes.push(("msec".into(), millis(duration)));
+            ("Timeout", vec![child])
+        }
+        BehaviourNode::Delay { duration, child } => {
+            attributes.push(("delay_msec".into(), millis(duration)));
+            ("Delay", vec![child])
+        }
+        BehaviourNode::Cooldown { .. } => return Err(XmlExportError::UnsupportedNode("Cooldown")),
+    };
+
+    let indent = INDENT.repeat(depth);
+    out.push_str(&format!("{indent}<{name}"));
+    for (key, value) in attributes {
+        out.push_str(&format!(" {key}=\"{}\"", escape(value.as_str())));
+    }
+    if children.is_empty() {
+        out.push_str("/>\n");
+        return Ok(());
+    }
+    out.push_str(">\n");
+    for child in children {
+        export_node(child, depth + 1, out)?;
+    }
+    out.push_str(&format!("{indent}</{name}>\n"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        parameter::{Parameter, Parameters},
+        tree::{BehaviourNode as Node, ParallelPolicy},
+    };
+
+    use super::{XmlErrorKind, XmlExportError};
+
+    fn key(name: &str) -> Parameter {
+        Parameter::BlackboardKey(name.into())
+    }
+
+    fn error(source: &str) -> (usize, usize, XmlErrorKind) {
+        let error = Node::from_xml(source).unwrap_err();
+        (error.line(), error.column(), error.kind().clone())
+    }
+
+    #[test]
+    fn import() {
+        let source = r#"
+<root BTCPP_format="4" main_tree_to_execute="Guard">
+    <BehaviorTree ID="Guard">
+        <ReactiveFallback name="guard">
+            <Sequence>
+                <Condition ID="npc:sees_player"/>
+                <SubTree ID="Chase" target="{player}" speed="2.5"/>
+            </Sequence>
+            <Parallel success_count="1" failure_count="-1">
+                <Action ID="npc:patrol" route="north" laps="3"/>
+                <Timeout msec="1500">
+                    <Decorator ID="npc:log" level="{=}">
+                        <Shout line="halt!"/>
+                    </Decorator>
+                </Timeout>
+            </Parallel>
+        </ReactiveFallback>
+    </BehaviorTree>
+    <BehaviorTree ID="Chase">
+        <RetryUntilSuccessful num_attempts="3">
+            <Action ID="npc:move" to="{target}" speed="{speed}" pose="{pose}"/>
+        </RetryUntilSuccessful>
+    </BehaviorTree>
+    <TreeNodesModel>
+        <Action ID="Shout"/>
+    </TreeNodesModel>
+</root>
+"#;
+        let expected = Node::Root(Box::new(Node::ReactiveFallback {
+            children: vec![
+                Node::Sequence {
+                    children: vec![
+                        Node::Condition("npc:sees_player".into()),
+                        Node::RetryUntilSuccess {
+                            attempts: 3,
+                            child: Box::new(Node::Executor(
+                                "npc:move".into(),
+                                Parameters::new()
+                                    .with("to", key("player"))
+                                    .with("speed", 2.5)
+                                    .with("pose", key("pose")),
+                            )),
+                        },
+                    ],
+                },
+                Node::Parallel {
+                    policy: ParallelPolicy::RequireOne,
+                    children: vec![
+                        Node::Executor(
+                            "npc:patrol".into(),
+                            Parameters::new().with("route", "north").with("laps", 3),
+                        ),
+                        Node::Timeout {
+                            duration: Duration::from_millis(1500),
+                            child: Box::new(Node::Decorator {
+                                name: "npc:log".into(),
+                                parameters: Parameters::new().with("level", key("level")),
+                                child: Box::new(Node::Executor(
+                                    "Shout".into(),
+                                    Parameters::new().with("line", "halt!"),
+                                )),
+                            }),
+                        },
+                    ],
+                },
+            ],
+        }));
+        assert_eq!(Node::from_xml(source), Ok(expected));
+    }
+
+    #[test]
+    fn import_single_tree_and_parallel_thresholds() {
+        let source = r#"<root>
+  <BehaviorTree ID="Only">
+    <Parallel success_count="-1" failure_count="2">
+      <AlwaysRun/>
+      <Action ID="b"/>
+      <Action ID="c"/>
+    </Parallel>
+  </BehaviorTree>
+</root>"#;
+        assert_eq!(
+            Node::from_xml(source),
+            Ok(Node::Root(Box::new(Node::Parallel {
+                policy: ParallelPolicy::Threshold {
+                    success: 3,
+                    failure: 2
+                },
+                children: vec![
+                    Node::Executor("AlwaysRun".into(), Parameters::new()),
+                    Node::Executor("b".into(), Parameters::new()),
+                    Node::Executor("c".into(), Parameters::new()),
+                ],
+            })))
+        );
+    }
+
+    #[test]
+    fn import_errors() {
+        let tree = |body: &str| {
+            format!("<root>\n  <BehaviorTree ID=\"Main\">\n    {body}\n  </BehaviorTree>\n</root>")
+        };
+        assert_eq!(
+            error(&tree(
+                "<Sequence>\n      <Switch2 variable=\"{x}\"/>\n    </Sequence>"
+            )),
+            (4, 7, XmlErrorKind::UnsupportedElement("Switch2".into()))
+        );
+        // Builtins are rejected rather than taken for custom nodes, even when
+        // written like one.
+        assert_eq!(
+            error(&tree("<LoopInt queue=\"1;2\"><Action ID=\"a\"/></LoopInt>")).2,
+            XmlErrorKind::UnsupportedElement("LoopInt".into())
+        );
+        assert_eq!(
+            error(&tree("<UnsetBlackboard key=\"x\"/>")).2,
+            XmlErrorKind::UnsupportedElement("UnsetBlackboard".into())
+        );
+        assert_eq!(
+            error(&tree(
+                "<Parallel threshold=\"2\"><Action ID=\"a\"/></Parallel>"
+            )),
+            (
+                3,
+                5,
+                XmlErrorKind::UnsupportedAttribute {
+                    element: "Parallel".into(),
+                    attribute: "threshold".into()
+                }
+            )
+        );
+        assert_eq!(
+            error(&tree("<Repeat><Action ID=\"a\"/></Repeat>")),
+            (
+                3,
+                5,
+                XmlErrorKind::MissingAttribute {
+                    element: "Repeat".into(),
+                    attribute: "num_cycles".into()
+                }
+            )
+        );
+        assert_eq!(
+            error(&tree(
+                "<Repeat num_cycles=\"-1\"><Action ID=\"a\"/></Repeat>"
+            ))
+            .2,
+            XmlErrorKind::InvalidAttribute {
+                element: "Repeat".into(),
+                attribute: "num_cycles".into(),
+                value: "-1".into()
+            }
+        );
+        assert_eq!(
+            error(&tree("<Inverter/>")).2,
+            XmlErrorKind::InvalidChildCount {
+                element: "Inverter".into(),
+                count: 0
+            }
+        );
+        assert_eq!(
+            error(&tree("<Condition ID=\"c\" range=\"3\"/>")).2,
+            XmlErrorKind::UnsupportedAttribute {
+                element: "Condition".into(),
+                attribute: "range".into()
+            }
+        );
+        assert_eq!(
+            error(&tree("<SubTree ID=\"Missing\"/>")).2,
+            XmlErrorKind::UnknownTree("Missing".into())
+        );
+        assert_eq!(
+            error(&tree("<Sequence><SubTree ID=\"Main\"/></Sequence>")).2,
+            XmlErrorKind::RecursiveSubTree("Main".into())
+        );
+        assert!(matches!(
+            error(&tree("<Sequence></Fallback>")),
+            (3, _, XmlErrorKind::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn error_messages() {
+        let tree =
+            |body: &str| format!("<root><BehaviorTree ID=\"Main\">{body}</BehaviorTree></root>");
+        let message = |body: &str| Node::from_xml(&tree(body)).unwrap_err().to_string();
+
+        assert_eq!(
+            message("<Repeat><Action ID=\"a\"/></Repeat>"),
+            "1:31: <Repeat> is missing the attribute `num_cycles`"
+        );
+        assert_eq!(
+            message("<SequenceStar><Action ID=\"a\"/></SequenceStar>"),
+            "1:31: <SequenceStar> is not supported"
+        );
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let subject = Node::Root(Box::new(Node::Sequence {
+            children: vec![
+                Node::Parallel {
+                    policy: ParallelPolicy::Threshold {
+                        success: 2,
+                        failure: 1,
+                    },
+                    children: vec![
+                        Node::Condition("npc:sees_player".into()),
+                        Node::Executor(
+                            "npc:say".into(),
+                            Parameters::new()
+                                .with("line", "<halt & \"stay\">")
+                                .with("volume", 0.5)
+                                .with("times", 2)
+                                .with("target", key("player")),
+                        ),
+                    ],
+                },
+                Node::Decorator {
+                    name: "npc:log".into(),
+                    parameters: Parameters::new(),
+                    child: Box::new(Node::Repeat {
+                        count: 2,
+                        child: Box::new(Node::Delay {
+                            duration: Duration::from_millis(250),
+                            child: Box::new(Node::UntilFailure {
+                                child: Box::new(Node::Executor("walk".into(), Parameters::new())),
+                            }),
+                        }),
+                    }),
+                },
+            ],
+        }));
+        let xml = subject.to_xml().unwrap();
+        assert!(xml.contains("<Action ID=\"game:walk\"/>"));
+        assert_eq!(Node::from_xml(&xml), Ok(subject));
+    }
+
+    #[test]
+    fn export_errors() {
+        let cooldown = Node::Cooldown {
+            duration: Duration::from_secs(1),
+            child: Box::new(Node::Condition("c".into())),
+        };
+        assert_eq!(
+            cooldown.to_xml(),
+            Err(XmlExportError::UnsupportedNode("Cooldown"))
+        );
+
+        assert_eq!(
+            cooldown.to_xml().unwrap_err().to_string(),
+            "Cooldown nodes have no BehaviorTree.CPP equivalent"
+        );
+
+        for parameter in [Parameter::Identifier("npc:guard".into()), "12".into()] {
+            let subject = Node::Executor("say".into(), Parameters::new().with("who", parameter));
+            assert_eq!(
+                subject.to_xml(),
+                Err(XmlExportError::UnsupportedParameter {
+                    node: "say".into(),
+                    parameter: "who".into()
+                })
+            );
+        }
+        assert_eq!(
+            Node::Executor("say".into(), Parameters::new().with("who", "12"))
+                .to_xml()
+                .unwrap_err()
+                .to_string(),
+            "the parameter `who` of `game:say` would not import as the same value"
+        );
+    }
+}